@@ -0,0 +1,278 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// A raw response body as it was received, with the moment it was stored.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CacheEntry {
+    pub body: String,
+    pub stored_at: SystemTime,
+}
+
+impl CacheEntry {
+    /// How long ago the entry was stored. A clock that went backwards counts as zero.
+    pub fn age(&self) -> Duration {
+        SystemTime::now().duration_since(self.stored_at).unwrap_or_default()
+    }
+}
+
+/// Where a response came from and how old it is. Fresh network responses have an age of zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheInfo {
+    pub from_cache: bool,
+    pub age: Duration,
+}
+
+impl CacheInfo {
+    /// The function `merge` combines the info of several responses that make up one result:
+    /// it is only served from cache if every part was, and it is as old as its oldest part.
+    pub fn merge(self, other: CacheInfo) -> CacheInfo {
+        CacheInfo { from_cache: self.from_cache && other.from_cache, age: self.age.max(other.age) }
+    }
+}
+
+/// Storage behind a `ResponseCache`.
+pub trait CacheBackend: fmt::Debug + Send + Sync {
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+    fn put(&self, key: &str, entry: CacheEntry);
+}
+
+/// Keeps up to `capacity` entries in memory, evicting the least recently used one first.
+#[derive(Debug)]
+pub struct MemoryCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    entries: HashMap<String, CacheEntry>,
+    /// Keys from least to most recently used.
+    order: VecDeque<String>,
+}
+
+impl LruState {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|existing| existing == key) {
+            if let Some(key) = self.order.remove(position) {
+                self.order.push_back(key);
+            }
+        }
+    }
+}
+
+impl MemoryCache {
+    /// The function `new` creates an empty cache. A `capacity` of zero is treated as one.
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache { capacity: capacity.max(1), state: Mutex::new(LruState::default()) }
+    }
+
+    /// Number of entries currently held.
+    pub fn len(&self) -> usize {
+        self.state.lock().map(|state| state.entries.len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let mut state = self.state.lock().ok()?;
+        let entry = state.entries.get(key).cloned()?;
+        state.touch(key);
+        Some(entry)
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        let Ok(mut state) = self.state.lock() else { return };
+        if state.entries.insert(key.to_string(), entry).is_some() {
+            state.touch(key);
+            return;
+        }
+        state.order.push_back(key.to_string());
+        while state.entries.len() > self.capacity {
+            match state.order.pop_front() {
+                Some(oldest) => state.entries.remove(&oldest),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Keeps every entry as a small JSON file in `dir`, so cached responses survive restarts.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+#[derive(Deserialize, Serialize)]
+struct DiskEntry {
+    key: String,
+    #[serde(flatten)]
+    entry: CacheEntry,
+}
+
+impl DiskCache {
+    /// The function `new` uses `dir`, creating it when it does not exist yet.
+    pub fn new(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(DiskCache { dir: dir.as_ref().to_path_buf() })
+    }
+
+    /// The cache directory inside the XDG cache directory, usually `~/.cache/task/responses`.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("responses"))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // FNV-1a, so file names stay the same across builds and Rust versions.
+        let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3));
+        self.dir.join(format!("{:016x}.json", hash))
+    }
+}
+
+impl CacheBackend for DiskCache {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        let stored: DiskEntry = serde_json::from_str(&contents).ok()?;
+        (stored.key == key).then_some(stored.entry)
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        let stored = DiskEntry { key: key.to_string(), entry };
+        let result = serde_json::to_string(&stored)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| fs::write(self.path(key), contents));
+        if let Err(err) = result {
+            warn!("Response could not be written to the disk cache in {}: {}", self.dir.display(), err);
+        }
+    }
+}
+
+/// How long responses of every endpoint stay fresh. A zero duration disables caching for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheTtl {
+    pub latest: Duration,
+    pub convert: Duration,
+    pub historical: Duration,
+    pub timeseries: Duration,
+    pub currencies: Duration,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        CacheTtl {
+            latest: Duration::from_secs(60),
+            convert: Duration::from_secs(60),
+            // Past rates do not change any more.
+            historical: Duration::from_secs(24 * 60 * 60),
+            timeseries: Duration::from_secs(24 * 60 * 60),
+            currencies: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl CacheTtl {
+    /// The function `for_endpoint` looks up the TTL of an endpoint path such as `/v1/latest`.
+    /// Unknown endpoints are never cached.
+    pub fn for_endpoint(&self, endpoint: &str) -> Duration {
+        match endpoint {
+            "/v1/latest" => self.latest,
+            "/v1/convert" => self.convert,
+            "/v1/historical" => self.historical,
+            "/v1/timeseries" => self.timeseries,
+            "/v1/currencies" => self.currencies,
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Settings of the response cache of a `CurrencyBeaconClient`.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    pub ttl: CacheTtl,
+    /// How many responses the in-memory LRU keeps.
+    pub capacity: usize,
+    /// Also keeps responses on disk in this directory when set.
+    pub disk_dir: Option<PathBuf>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig { ttl: CacheTtl::default(), capacity: 256, disk_dir: None }
+    }
+}
+
+/// A TTL cache of raw response bodies keyed by endpoint and normalized parameters. Lookups
+/// try memory first and then disk; a disk hit is copied back into memory.
+#[derive(Debug)]
+pub struct ResponseCache {
+    ttl: CacheTtl,
+    memory: MemoryCache,
+    disk: Option<DiskCache>,
+}
+
+impl ResponseCache {
+    /// The function `new` sets the cache up as `config` says.
+    ///
+    /// Returns:
+    ///
+    /// The cache, or the IO error raised while creating the disk cache directory.
+    pub fn new(config: CacheConfig) -> std::io::Result<Self> {
+        let disk = match &config.disk_dir {
+            Some(dir) => Some(DiskCache::new(dir)?),
+            None => None,
+        };
+        Ok(ResponseCache { ttl: config.ttl, memory: MemoryCache::new(config.capacity), disk })
+    }
+
+    /// The function `key` builds the cache key of a request. Parameters are sorted, trimmed and
+    /// upper cased, comma separated lists are sorted too, and the api key is never part of it.
+    pub fn key(endpoint: &str, params: &[(&str, String)]) -> String {
+        let mut normalized: Vec<(String, String)> = params.iter()
+            .filter(|(name, _)| *name != "api_key")
+            .map(|(name, value)| {
+                let mut items: Vec<String> = value.split(',').map(|item| item.trim().to_ascii_uppercase()).collect();
+                items.sort();
+                (name.to_ascii_lowercase(), items.join(","))
+            })
+            .collect();
+        normalized.sort();
+        let query: Vec<String> = normalized.into_iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        format!("{}?{}", endpoint, query.join("&"))
+    }
+
+    /// The function `get` returns the stored body of `key` when it is younger than the TTL of `endpoint`.
+    pub fn get(&self, endpoint: &str, key: &str) -> Option<CacheEntry> {
+        let ttl = self.ttl.for_endpoint(endpoint);
+        if ttl.is_zero() {
+            return None;
+        }
+        if let Some(entry) = self.memory.get(key).filter(|entry| entry.age() < ttl) {
+            return Some(entry);
+        }
+        let entry = self.disk.as_ref()?.get(key).filter(|entry| entry.age() < ttl)?;
+        self.memory.put(key, entry.clone());
+        Some(entry)
+    }
+
+    /// The function `put` stores `body` under `key` unless caching is disabled for `endpoint`.
+    pub fn put(&self, endpoint: &str, key: &str, body: &str) {
+        if self.ttl.for_endpoint(endpoint).is_zero() {
+            return;
+        }
+        let entry = CacheEntry { body: body.to_string(), stored_at: SystemTime::now() };
+        if let Some(disk) = &self.disk {
+            disk.put(key, entry.clone());
+        }
+        self.memory.put(key, entry);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Whether a `Cassette` stores what the API answers or answers in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Sends every request and appends the exchange to the cassette file, which is started afresh.
+    Record,
+    /// Sends nothing and answers every request from the cassette file.
+    Replay,
+}
+
+/// Settings of the cassette of a `CurrencyBeaconClient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CassetteConfig {
+    pub path: PathBuf,
+    pub mode: CassetteMode,
+}
+
+impl CassetteConfig {
+    /// The function `record` records into the cassette at `path`.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        CassetteConfig { path: path.into(), mode: CassetteMode::Record }
+    }
+
+    /// The function `replay` replays the cassette at `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> Self {
+        CassetteConfig { path: path.into(), mode: CassetteMode::Replay }
+    }
+}
+
+/// One request and the response it got. The API key is never part of it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Interaction {
+    /// The endpoint path, e.g. `/v1/convert`, empty for the API root.
+    pub endpoint: String,
+    pub params: BTreeMap<String, String>,
+    pub status: u16,
+    /// The `Retry-After` of the response in seconds, if it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+    /// The body as JSON, so cassettes stay readable; a body that is not JSON is kept as a string.
+    pub body: Value,
+}
+
+impl Interaction {
+    /// The body as it was received.
+    pub fn body_text(&self) -> String {
+        match &self.body {
+            Value::String(text) => text.clone(),
+            json => json.to_string(),
+        }
+    }
+
+    fn matches(&self, endpoint: &str, params: &BTreeMap<String, String>) -> bool {
+        self.endpoint == endpoint && self.params == *params
+    }
+}
+
+/// The contents of a cassette file.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    tape: Tape,
+    /// Whether each interaction has been replayed already.
+    played: Vec<bool>,
+}
+
+/// Request and response pairs kept in a JSON file, so tests can run against real answers of the
+/// API without network access. Record a cassette once against the live API, then replay it.
+///
+/// Replaying answers a request with the first recorded response to the same endpoint and
+/// parameters that has not been replayed yet, so retries and repeated requests get their
+/// responses in the recorded order. Once they are used up the last of them keeps being served.
+#[derive(Debug)]
+pub struct Cassette {
+    config: CassetteConfig,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// The function `open` starts an empty cassette for recording or loads one for replaying.
+    ///
+    /// Returns:
+    ///
+    /// The cassette, or the IO error raised while writing the new cassette file or reading one
+    /// to replay, which has to exist.
+    pub fn open(config: CassetteConfig) -> std::io::Result<Self> {
+        let tape = match config.mode {
+            CassetteMode::Record => {
+                let tape = Tape::default();
+                write_tape(&config.path, &tape)?;
+                tape
+            }
+            CassetteMode::Replay => {
+                let contents = fs::read_to_string(&config.path)?;
+                serde_json::from_str(&contents).map_err(std::io::Error::from)?
+            }
+        };
+        let played = vec![false; tape.interactions.len()];
+        Ok(Cassette { config, state: Mutex::new(CassetteState { tape, played }) })
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.config.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.config.path
+    }
+
+    /// Every interaction on the cassette, in the order they were recorded.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state().tape.interactions.clone()
+    }
+
+    /// The function `record` appends a response to the cassette and writes it to the file. An
+    /// `api_key` among `params` is left out.
+    pub fn record(&self, endpoint: &str, params: &[(&str, String)], status: u16, retry_after: Option<Duration>, body: &str) {
+        let interaction = Interaction {
+            endpoint: endpoint.to_string(),
+            params: query(params),
+            status,
+            retry_after: retry_after.map(|retry_after| retry_after.as_secs()),
+            body: serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string())),
+        };
+        let mut state = self.state();
+        state.tape.interactions.push(interaction);
+        state.played.push(false);
+        if let Err(err) = write_tape(&self.config.path, &state.tape) {
+            warn!("API cassette could not be written to {}: {}", self.config.path.display(), err);
+        }
+    }
+
+    /// The function `replay` looks up the recorded response to a request. An `api_key` among
+    /// `params` is ignored.
+    ///
+    /// Returns:
+    ///
+    /// The interaction, or `None` when the request was never recorded.
+    pub fn replay(&self, endpoint: &str, params: &[(&str, String)]) -> Option<Interaction> {
+        let params = query(params);
+        let mut state = self.state();
+        let matching: Vec<usize> = state.tape.interactions.iter().enumerate()
+            .filter(|(_, interaction)| interaction.matches(endpoint, &params))
+            .map(|(index, _)| index)
+            .collect();
+        let index = matching.iter().copied().find(|index| !state.played[*index]).or(matching.last().copied())?;
+        state.played[index] = true;
+        Some(state.tape.interactions[index].clone())
+    }
+
+    fn state(&self) -> MutexGuard<'_, CassetteState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// The function `query` keys the parameters of a request by name, without the API key.
+fn query(params: &[(&str, String)]) -> BTreeMap<String, String> {
+    params.iter()
+        .filter(|(key, _)| *key != "api_key")
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect()
+}
+
+fn write_tape(path: &Path, tape: &Tape) -> std::io::Result<()> {
+    let contents = serde_json::to_string_pretty(tape).map_err(std::io::Error::from)?;
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents + "\n")
+}
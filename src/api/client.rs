@@ -0,0 +1,519 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use log::{debug, error, info, warn};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+
+use super::cache::{CacheConfig, CacheInfo, ResponseCache};
+use super::cassette::{Cassette, CassetteConfig, CassetteMode};
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments, TimeSeriesArguments};
+use super::credentials::Credentials;
+use super::rate_limit::{LimitMode, RateLimit, RateLimiter};
+use super::retry::{parse_retry_after, RetryPolicy};
+use super::usage::{UsageConfig, UsageReport, UsageTracker};
+use super::money::{Money, RoundingPolicy};
+use super::provider::{parse_day, Conversion, Currencies, CurrencyInfo, ProviderFuture, RateProvider, Rates};
+use super::models::{ConvertResponse, CurrencyList, Envelope, Health, HistoricalResponse, LatestResponse, TimeSeriesResponse};
+use super::timeseries::{DateChunks, TimeSeriesChunk, TIMESERIES_MAX_DAYS};
+use super::{ApiError, CurrencyListRequest, ExchangeCurrencyRequest, HistoricalRatesRequest, RatiosListRequest, Requests, Result};
+
+/// Tunables of the HTTP connection pool behind a `CurrencyBeaconClient`.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// How long establishing a TCP/TLS connection may take.
+    pub connect_timeout: Duration,
+    /// How long a whole request may take once it has been sent, reading the body included.
+    /// reqwest 0.11 has no separate per-read timeout, so this bounds the exchange as a whole.
+    pub read_timeout: Duration,
+    /// Sent as the `User-Agent` header with every request.
+    pub user_agent: String,
+    /// Idle connections kept open per host for reuse.
+    pub pool_max_idle_per_host: usize,
+    /// Serves repeated requests from a TTL cache when set. `None` sends every request.
+    pub cache: Option<CacheConfig>,
+    /// How failed requests are retried, see `CurrencyBeaconClient::with_retry` to change it per request.
+    pub retry: RetryPolicy,
+    /// Keeps requests under the plan's rate when set. Cache hits do not count, retries do.
+    pub rate_limit: Option<RateLimit>,
+    /// Counts every request sent against the monthly allowance when set.
+    pub usage: Option<UsageConfig>,
+    /// Records every exchange with the API into a cassette file, or answers from one without
+    /// any network, when set.
+    pub cassette: Option<CassetteConfig>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            connect_timeout: Duration::from_secs(5),
+            read_timeout: Duration::from_secs(15),
+            user_agent: concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")).to_string(),
+            pool_max_idle_per_host: 8,
+            cache: None,
+            retry: RetryPolicy::default(),
+            rate_limit: None,
+            usage: None,
+            cassette: None,
+        }
+    }
+}
+
+/// A CurrencyBeacon client owning a configured `reqwest::Client` and the credentials.
+///
+/// Cloning is cheap and clones share the same connection pool, so a service doing many
+/// conversions should build one client and hand it around.
+#[derive(Debug, Clone)]
+pub struct CurrencyBeaconClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    cache: Option<Arc<ResponseCache>>,
+    retry: RetryPolicy,
+    limiter: Option<Arc<RateLimiter>>,
+    limit_mode: LimitMode,
+    usage: Option<Arc<UsageTracker>>,
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl CurrencyBeaconClient {
+    /// The function `new` builds a client with the default `ClientConfig`.
+    ///
+    /// Returns:
+    ///
+    /// A ready to use client, or `ApiError::InvalidArguments` when the API url is not valid.
+    pub fn new(credentials: &Credentials) -> Result<Self> {
+        Self::with_config(credentials, ClientConfig::default())
+    }
+
+    /// The function `with_config` builds a client whose connection pool is tuned by `config`.
+    ///
+    /// Returns:
+    ///
+    /// A ready to use client, or `ApiError::InvalidArguments` when the API url is not valid
+    /// or the HTTP client or its cache could not be set up.
+    pub fn with_config(credentials: &Credentials, config: ClientConfig) -> Result<Self> {
+        let base_url = credentials.url.trim_end_matches('/').to_string();
+        reqwest::Url::parse(&base_url)
+            .map_err(|err| ApiError::invalid_arguments(format!("{} is not a valid API url", base_url), err))?;
+        let http = reqwest::Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.read_timeout)
+            .user_agent(config.user_agent)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .build()
+            .map_err(|err| ApiError::invalid_arguments("HTTP client could not be built", err))?;
+        let cache = match config.cache {
+            Some(cache) => Some(Arc::new(ResponseCache::new(cache)
+                .map_err(|err| ApiError::invalid_arguments("response cache could not be set up", err))?)),
+            None => None,
+        };
+        let usage = match config.usage {
+            Some(usage) => Some(Arc::new(UsageTracker::open(usage)
+                .map_err(|err| ApiError::invalid_arguments("API usage state could not be loaded", err))?)),
+            None => None,
+        };
+        let cassette = match config.cassette {
+            Some(cassette) => Some(Arc::new(Cassette::open(cassette)
+                .map_err(|err| ApiError::invalid_arguments("API cassette could not be opened", err))?)),
+            None => None,
+        };
+        let limiter = config.rate_limit.as_ref().map(|limit| Arc::new(RateLimiter::new(limit)));
+        let limit_mode = config.rate_limit.map(|limit| limit.mode).unwrap_or_default();
+        Ok(CurrencyBeaconClient {
+            http,
+            base_url,
+            api_key: credentials.api_key.clone(),
+            cache,
+            retry: config.retry,
+            limiter,
+            limit_mode,
+            usage,
+            cassette,
+        })
+    }
+
+    /// The function `with_retry` returns a client that retries as `retry` says. It shares the
+    /// connection pool and the cache with this one, so it is cheap enough to make per request:
+    /// `ExchangeCurrencyRequest { client: &client.with_retry(RetryPolicy::none()), .. }`.
+    pub fn with_retry(&self, retry: RetryPolicy) -> Self {
+        CurrencyBeaconClient { retry, ..self.clone() }
+    }
+
+    /// The function `with_limit_mode` returns a client that, when the rate limit is reached,
+    /// waits or fails fast as `mode` says. It shares the limiter with this one, so both count
+    /// against the same limit.
+    pub fn with_limit_mode(&self, mode: LimitMode) -> Self {
+        CurrencyBeaconClient { limit_mode: mode, ..self.clone() }
+    }
+
+    /// The function `usage` reports how many requests this client and earlier runs sharing its
+    /// state file have sent in the current month.
+    ///
+    /// Returns:
+    ///
+    /// The report, or `None` when the client does not track usage.
+    pub fn usage(&self) -> Option<UsageReport> {
+        self.usage.as_ref().map(|usage| usage.current())
+    }
+
+    /// The cassette the client records into or replays from, if any.
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_deref()
+    }
+
+    /// The API root every endpoint is resolved against.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// The function `convert` asks `/v1/convert` to exchange `args.amount` of `args.from` into `args.to`.
+    /// With `args.date` set, the rate of that day is taken from `/v1/historical` instead, because
+    /// `/v1/convert` only knows the current rate.
+    ///
+    /// Returns:
+    ///
+    /// The conversion with its value rounded by `args.rounding`, or `ApiError::UnknownCurrency` when
+    /// either currency does not exist.
+    pub async fn convert(&self, args: &ExchangeCurrencyArguments) -> Result<ConvertResponse> {
+        let from = args.from.to_ascii_uppercase();
+        let to = args.to.to_ascii_uppercase();
+        if let Some(date) = args.date {
+            // The base is never among its own rates, so converting into it asks for all of them,
+            // which only tells that it exists, and takes a rate of 1.
+            let historical = self.historical(&HistoricalRatesArguments {
+                date,
+                base: from.clone(),
+                symbols: if from == to { None } else { Some(vec![to.clone()]) },
+            }).await?;
+            let rate = if from == to {
+                Decimal::ONE
+            } else {
+                historical.rates.get(&to).copied()
+                    .ok_or_else(|| ApiError::UnknownCurrency { codes: vec![to.clone()] })?
+            };
+            let value = Money::new(args.amount, &from).checked_convert(rate, &to)?.round(args.rounding).amount;
+            return Ok(ConvertResponse {
+                timestamp: historical.date.and_hms_opt(0, 0, 0).map(|time| time.and_utc().timestamp()).unwrap_or_default(),
+                date: historical.date.to_string(),
+                from,
+                to,
+                amount: args.amount,
+                value: Some(value),
+                cache: historical.cache,
+            });
+        }
+        let (response, cache): (ConvertResponse, CacheInfo) = self.get("/v1/convert", &[
+            ("from", from.clone()),
+            ("to", to.clone()),
+            ("amount", args.amount.to_string()),
+        ]).await?;
+        match response.value {
+            Some(value) => Ok(ConvertResponse { value: Some(args.rounding.apply(value, &to)), cache, ..response }),
+            None => Err(ApiError::UnknownCurrency { codes: vec![from, to] }),
+        }
+    }
+
+    /// The function `latest` fetches the current rates of every currency against `args.base`.
+    ///
+    /// Returns:
+    ///
+    /// The rates, or `ApiError::UnknownCurrency` when the base currency does not exist.
+    pub async fn latest(&self, args: &RatiosListArguments) -> Result<LatestResponse> {
+        let base = args.base.to_ascii_uppercase();
+        let (response, cache): (LatestResponse, CacheInfo) = self.get("/v1/latest", &[("base", base.clone())]).await?;
+        if response.rates.is_empty() {
+            return Err(ApiError::UnknownCurrency { codes: vec![base] });
+        }
+        Ok(LatestResponse { cache, ..response })
+    }
+
+    /// The function `historical` fetches the rates of every currency, or only of `args.symbols`,
+    /// against `args.base` as they were on `args.date`.
+    ///
+    /// Returns:
+    ///
+    /// The rates together with the date they are effective for, or `ApiError::UnknownCurrency`
+    /// when the base currency does not exist.
+    pub async fn historical(&self, args: &HistoricalRatesArguments) -> Result<HistoricalResponse> {
+        let base = args.base.to_ascii_uppercase();
+        let mut params = vec![("base", base.clone()), ("date", args.date.format("%Y-%m-%d").to_string())];
+        if let Some(symbols) = &args.symbols {
+            let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.trim().to_ascii_uppercase()).collect();
+            params.push(("symbols", symbols.join(",")));
+        }
+        let (response, cache): (HistoricalResponse, CacheInfo) = self.get("/v1/historical", &params).await?;
+        if response.rates.is_empty() {
+            return Err(ApiError::UnknownCurrency { codes: vec![base] });
+        }
+        Ok(HistoricalResponse { cache, ..response })
+    }
+
+    /// The function `timeseries` fetches the daily rates against `args.base` from `args.start_date`
+    /// to `args.end_date`. Ranges longer than `TIMESERIES_MAX_DAYS` are split into several calls,
+    /// made one after another, and merged in date order.
+    ///
+    /// Returns:
+    ///
+    /// The rates per day, `ApiError::InvalidArguments` when the range ends before it starts, or
+    /// `ApiError::UnknownCurrency` when the API had no rates at all for the base currency.
+    pub async fn timeseries(&self, args: &TimeSeriesArguments) -> Result<TimeSeriesResponse> {
+        if args.start_date > args.end_date {
+            return Err(ApiError::InvalidArguments {
+                reason: format!("start date {} is after end date {}", args.start_date, args.end_date),
+                source: None,
+            });
+        }
+        let base = args.base.to_ascii_uppercase();
+        let symbols: Option<String> = args.symbols.as_ref().map(|symbols| {
+            symbols.iter().map(|symbol| symbol.trim().to_ascii_uppercase()).collect::<Vec<String>>().join(",")
+        });
+        let mut series = TimeSeriesResponse {
+            base: base.clone(),
+            start_date: args.start_date,
+            end_date: args.end_date,
+            rates: Default::default(),
+            cache: CacheInfo { from_cache: true, age: Duration::ZERO },
+        };
+        for (start, end) in DateChunks::new(args.start_date, args.end_date, TIMESERIES_MAX_DAYS) {
+            let mut params = vec![
+                ("base", base.clone()),
+                ("start_date", start.format("%Y-%m-%d").to_string()),
+                ("end_date", end.format("%Y-%m-%d").to_string()),
+            ];
+            if let Some(symbols) = &symbols {
+                params.push(("symbols", symbols.clone()));
+            }
+            let (chunk, cache): (TimeSeriesChunk, CacheInfo) = self.get("/v1/timeseries", &params).await?;
+            series.cache = series.cache.merge(cache);
+            series.rates.extend(chunk.days.into_iter()
+                .filter(|(day, daily)| *day >= start && *day <= end && !daily.rates.is_empty())
+                .map(|(day, daily)| (day, daily.rates)));
+        }
+        if series.rates.is_empty() {
+            return Err(ApiError::UnknownCurrency { codes: vec![base] });
+        }
+        Ok(series)
+    }
+
+    /// The function `currencies` lists every currency known to the API.
+    pub async fn currencies(&self) -> Result<CurrencyList> {
+        let (response, cache): (CurrencyList, CacheInfo) = self.get("/v1/currencies", &[]).await?;
+        Ok(CurrencyList { cache, ..response })
+    }
+
+    /// The function `count_request` counts a request to `endpoint` against the quota and, when it
+    /// is time, writes the counts to the state file on a blocking thread.
+    fn count_request(&self, endpoint: &str) {
+        let Some(usage) = &self.usage else { return };
+        usage.record(endpoint);
+        if usage.flush_due() {
+            let usage = usage.clone();
+            tokio::task::spawn_blocking(move || usage.flush());
+        }
+    }
+
+    /// The function `health` pings the API root to check out aliveness of the service.
+    ///
+    /// Returns:
+    ///
+    /// Always `Ok`; an unreachable API is reported through `Health::reachable`.
+    pub async fn health(&self) -> Result<Health> {
+        let request = self.url("", &[])?;
+        if let Some(cassette) = self.replaying() {
+            let status = cassette.replay("", &[]).map(|interaction| interaction.status);
+            return Ok(Health { reachable: status.is_some(), status });
+        }
+        self.count_request("/");
+        match self.http.get(request).send().await {
+            Ok(resp) => {
+                info!("Request was sent to {}", self.base_url);
+                if let Some(cassette) = &self.cassette {
+                    cassette.record("", &[], resp.status().as_u16(), None, "");
+                }
+                Ok(Health { reachable: true, status: Some(resp.status().as_u16()) })
+            }
+            Err(err) => {
+                error!("Request got an error: {}", err);
+                Ok(Health { reachable: false, status: err.status().map(|status| status.as_u16()) })
+            }
+        }
+    }
+
+    /// The cassette requests are answered from instead of the network, if the client replays one.
+    fn replaying(&self) -> Option<&Cassette> {
+        self.cassette.as_deref().filter(|cassette| cassette.mode() == CassetteMode::Replay)
+    }
+
+    /// The function `url` joins `endpoint` onto the base url and appends the api key and `params`.
+    fn url(&self, endpoint: &str, params: &[(&str, String)]) -> Result<reqwest::Url> {
+        let url = self.base_url.clone() + endpoint;
+        let query = std::iter::once(("api_key", self.api_key.as_str()))
+            .chain(params.iter().map(|(key, value)| (*key, value.as_str())));
+        reqwest::Url::parse_with_params(&url, query)
+            .map_err(|err| ApiError::invalid_arguments(format!("{} is not a valid API url", url), err))
+    }
+
+    /// The function `get` sends a GET request to `endpoint` and decodes the `response` part of the payload.
+    /// When the client has a cache, a fresh cached body is used instead of sending the request, and
+    /// successful bodies are stored for the next call. Failed attempts are retried by the retry policy.
+    ///
+    /// Returns:
+    ///
+    /// The decoded value and where it came from, or an `ApiError` describing whether the transport,
+    /// the HTTP status, the API key, the quota or the payload itself was at fault.
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str, params: &[(&str, String)]) -> Result<(T, CacheInfo)> {
+        let key = ResponseCache::key(endpoint, params);
+        if let Some(cache) = &self.cache {
+            if let Some(entry) = cache.get(endpoint, &key) {
+                if let Ok(envelope) = serde_json::from_str::<Envelope<T>>(&entry.body) {
+                    debug!("GET {} served from cache, {:?} old", key, entry.age());
+                    return Ok((envelope.response, CacheInfo { from_cache: true, age: entry.age() }));
+                }
+            }
+        }
+        let mut attempt: u32 = 1;
+        loop {
+            if self.replaying().is_none() {
+                if let Some(limiter) = &self.limiter {
+                    limiter.acquire(self.limit_mode).await?;
+                }
+                self.count_request(endpoint);
+            }
+            match self.send(endpoint, params).await {
+                Ok((response, body)) => {
+                    if let Some(cache) = &self.cache {
+                        cache.put(endpoint, &key, &body);
+                    }
+                    return Ok((response, CacheInfo::default()));
+                }
+                Err(err) => match self.retry.delay(attempt, &err) {
+                    Some(delay) => {
+                        warn!("GET {} failed on attempt {}/{}: {}, retrying in {:?}", endpoint, attempt, self.retry.max_attempts, err, delay);
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(err),
+                },
+            }
+        }
+    }
+
+    /// The function `send` makes a single attempt at `endpoint`, without cache or retries. A
+    /// replayed cassette answers in place of the API, a recorded one keeps the response.
+    ///
+    /// Returns:
+    ///
+    /// The decoded value together with the raw body it was decoded from.
+    async fn send<T: DeserializeOwned>(&self, endpoint: &str, params: &[(&str, String)]) -> Result<(T, String)> {
+        let request = self.url(endpoint, params)?;
+        let (status, status_error, retry_after, body) = match self.replaying() {
+            Some(cassette) => {
+                let interaction = cassette.replay(endpoint, params).ok_or_else(|| ApiError::InvalidArguments {
+                    reason: format!("cassette {} has no response to GET {} {:?}", cassette.path().display(), endpoint, params),
+                    source: None,
+                })?;
+                debug!("GET {}{} replayed from {}", self.base_url, endpoint, cassette.path().display());
+                let status = reqwest::StatusCode::from_u16(interaction.status)
+                    .map_err(|err| ApiError::invalid_arguments(format!("cassette {} holds an invalid status", cassette.path().display()), err))?;
+                (status, None, interaction.retry_after.map(Duration::from_secs), interaction.body_text())
+            }
+            None => {
+                debug!("GET {}{}", self.base_url, endpoint);
+                let resp = self.http.get(request).send().await?;
+                let status = resp.status();
+                let status_error = resp.error_for_status_ref().err();
+                let retry_after = resp.headers().get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| parse_retry_after(value, SystemTime::now()));
+                let body = resp.text().await?;
+                if let Some(cassette) = &self.cassette {
+                    // The key is not among `params`, but an error message might quote it.
+                    let scrubbed = if self.api_key.is_empty() { body.clone() } else { body.replace(&self.api_key, "[api_key]") };
+                    cassette.record(endpoint, params, status.as_u16(), retry_after, &scrubbed);
+                }
+                (status, status_error, retry_after, body)
+            }
+        };
+        if let Some(err) = ApiError::from_response(status, &body, status_error) {
+            return Err(err.with_retry_after(retry_after));
+        }
+        match serde_json::from_str::<Envelope<T>>(&body) {
+            Ok(envelope) => Ok((envelope.response, body)),
+            Err(source) => Err(ApiError::MalformedPayload { body, source }),
+        }
+    }
+}
+
+/// CurrencyBeacon as a `RateProvider`, answering through the request structs of `task::api`.
+impl RateProvider for CurrencyBeaconClient {
+    fn name(&self) -> &str {
+        "currencybeacon"
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move {
+            let response = (RatiosListRequest { args, client: self }).call().await?;
+            Ok(Rates {
+                provider: self.name().to_string(),
+                base: response.base.to_ascii_uppercase(),
+                date: parse_day(&response.date),
+                rates: response.rates,
+                cache: response.cache,
+            })
+        })
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move {
+            let response = (HistoricalRatesRequest { args, client: self }).call().await?;
+            Ok(Rates {
+                provider: self.name().to_string(),
+                base: response.base.to_ascii_uppercase(),
+                date: response.date,
+                rates: response.rates,
+                cache: response.cache,
+            })
+        })
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        Box::pin(async move {
+            let rounding = args.rounding;
+            let from = Money::new(args.amount, &args.from);
+            // The value is asked for unrounded so the rate can be told from it, and rounded here.
+            // 28 places is all a `Decimal` can hold, so nothing is rounded away.
+            let args = ExchangeCurrencyArguments {
+                rounding: RoundingPolicy { places: Some(28), ..rounding },
+                ..args
+            };
+            let response = (ExchangeCurrencyRequest { args, client: self }).call().await?;
+            let value = response.converted()
+                .ok_or_else(|| ApiError::UnknownCurrency { codes: vec![from.currency.clone(), response.to.clone()] })?;
+            let rate = if from.amount.is_zero() { None } else { Some(value.amount / from.amount) };
+            Ok(Conversion {
+                provider: self.name().to_string(),
+                to: value.round(rounding),
+                from,
+                rate,
+                date: parse_day(&response.date),
+                cache: response.cache,
+            })
+        })
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        Box::pin(async move {
+            let response = (CurrencyListRequest { client: self }).call().await?;
+            let mut currencies: Vec<CurrencyInfo> = response.currencies.into_iter()
+                .map(|currency| CurrencyInfo {
+                    code: currency.short_code.to_ascii_uppercase(),
+                    name: Some(currency.name).filter(|name| !name.is_empty()),
+                })
+                .collect();
+            currencies.sort_by(|a, b| a.code.cmp(&b.code));
+            currencies.dedup_by(|a, b| a.code == b.code);
+            Ok(Currencies { provider: self.name().to_string(), currencies, cache: response.cache })
+        })
+    }
+}
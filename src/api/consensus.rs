@@ -0,0 +1,313 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use futures::future::join_all;
+use log::warn;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use super::cache::CacheInfo;
+use super::money::Money;
+use super::provider::{Conversion, Currencies, CurrencyInfo, ProviderFuture, RateProvider, Rates};
+use super::{ApiError, Result};
+
+/// How the quotes of several sources are combined into one rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Aggregation {
+    /// The middle quote, or the mean of the two middle ones. A single bad source cannot move it.
+    #[default]
+    Median,
+    /// The arithmetic mean of all quotes.
+    Mean,
+}
+
+impl Aggregation {
+    /// The function `combine` aggregates `quotes`, which must not be empty.
+    fn combine(self, quotes: &[Decimal]) -> Decimal {
+        match self {
+            Aggregation::Mean => quotes.iter().sum::<Decimal>() / Decimal::from(quotes.len()),
+            Aggregation::Median => {
+                let mut sorted = quotes.to_vec();
+                sorted.sort();
+                let middle = sorted.len() / 2;
+                if sorted.len() % 2 == 1 {
+                    sorted[middle]
+                } else {
+                    (sorted[middle - 1] + sorted[middle]) / Decimal::TWO
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Aggregation::Median => "median",
+            Aggregation::Mean => "mean",
+        })
+    }
+}
+
+impl FromStr for Aggregation {
+    type Err = String;
+
+    /// Accepts the names printed by `Display`, case insensitive, plus `average` for `Mean`.
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "median" => Ok(Aggregation::Median),
+            "mean" | "average" => Ok(Aggregation::Mean),
+            other => Err(format!("unknown aggregation '{}', expected median or mean", other)),
+        }
+    }
+}
+
+/// Settings of a `ConsensusProvider`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsensusConfig {
+    pub aggregation: Aggregation,
+    /// How far in percent a quote may be off the consensus rate before its source is flagged.
+    pub max_deviation: Decimal,
+}
+
+impl Default for ConsensusConfig {
+    fn default() -> Self {
+        ConsensusConfig { aggregation: Aggregation::default(), max_deviation: Decimal::ONE }
+    }
+}
+
+/// The quote of one source for one currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceQuote {
+    pub provider: String,
+    pub rate: Decimal,
+    /// How far the quote is off the consensus rate, in percent and rounded to 4 places.
+    pub deviation: Decimal,
+    /// Whether `deviation` is above `ConsensusConfig::max_deviation`.
+    pub flagged: bool,
+}
+
+/// The consensus rate of one currency with the quotes it was made of.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConsensusRate {
+    pub rate: Decimal,
+    pub sources: Vec<SourceQuote>,
+}
+
+/// A source that could not answer at all.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceFailure {
+    pub provider: String,
+    pub error: String,
+}
+
+/// Rates against `base` agreed on by several sources.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Consensus {
+    pub base: String,
+    /// The most recent day any source had rates for.
+    pub date: NaiveDate,
+    pub aggregation: Aggregation,
+    pub rates: BTreeMap<String, ConsensusRate>,
+    pub failures: Vec<SourceFailure>,
+}
+
+impl Consensus {
+    /// The function `flagged` lists the sources that were flagged for at least one currency.
+    pub fn flagged(&self) -> BTreeSet<&str> {
+        self.rates.values()
+            .flat_map(|rate| rate.sources.iter())
+            .filter(|quote| quote.flagged)
+            .map(|quote| quote.provider.as_str())
+            .collect()
+    }
+
+    /// The function `to_rates` keeps only the consensus rate of every currency.
+    pub fn to_rates(&self, provider: &str) -> Rates {
+        Rates {
+            provider: provider.to_string(),
+            base: self.base.clone(),
+            date: self.date,
+            rates: self.rates.iter().map(|(code, rate)| (code.clone(), rate.rate)).collect(),
+            cache: CacheInfo::default(),
+        }
+    }
+}
+
+/// Asks several providers at once and combines their quotes into one rate per currency,
+/// flagging the sources that are too far off it. A currency only one source quotes is taken
+/// as it is; sources that fail are listed in `Consensus::failures` as long as one answers.
+pub struct ConsensusProvider {
+    providers: Vec<Box<dyn RateProvider>>,
+    config: ConsensusConfig,
+}
+
+impl ConsensusProvider {
+    /// The function `new` aggregates `providers` with the default `ConsensusConfig`.
+    pub fn new(providers: Vec<Box<dyn RateProvider>>) -> Self {
+        ConsensusProvider { providers, config: ConsensusConfig::default() }
+    }
+
+    /// The function `with_config` replaces how quotes are combined and when they are flagged.
+    pub fn with_config(mut self, config: ConsensusConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The function `latest_consensus` combines the current rates of every provider.
+    ///
+    /// Returns:
+    ///
+    /// The consensus with the quote of every source, or the error of the last provider when none answered.
+    pub async fn latest_consensus(&self, args: RatiosListArguments) -> Result<Consensus> {
+        let base = args.base.trim().to_ascii_uppercase();
+        self.gather(base, |provider| provider.latest(args.clone())).await
+    }
+
+    /// The function `historical_consensus` combines the rates of every provider effective on `args.date`.
+    ///
+    /// Returns:
+    ///
+    /// The consensus with the quote of every source, or the error of the last provider when none answered.
+    pub async fn historical_consensus(&self, args: HistoricalRatesArguments) -> Result<Consensus> {
+        let base = args.base.trim().to_ascii_uppercase();
+        self.gather(base, |provider| provider.historical(args.clone())).await
+    }
+
+    /// The function `gather` asks every provider at once through `call` and combines their answers.
+    async fn gather<'a>(&'a self, base: String, call: impl Fn(&'a dyn RateProvider) -> ProviderFuture<'a, Rates>) -> Result<Consensus> {
+        let answers = join_all(self.providers.iter().map(|provider| call(provider.as_ref()))).await;
+        let mut quotes: BTreeMap<String, Vec<(String, Decimal)>> = BTreeMap::new();
+        let mut failures = Vec::new();
+        let mut date: Option<NaiveDate> = None;
+        let mut last_err = None;
+        for (provider, answer) in self.providers.iter().zip(answers) {
+            match answer {
+                Ok(rates) => {
+                    date = date.max(Some(rates.date));
+                    for (code, rate) in rates.rates {
+                        quotes.entry(code.to_ascii_uppercase()).or_default().push((rates.provider.clone(), rate));
+                    }
+                }
+                Err(err) => {
+                    failures.push(SourceFailure { provider: provider.name().to_string(), error: err.to_string() });
+                    last_err = Some(err);
+                }
+            }
+        }
+        let date = match (date, last_err) {
+            (Some(date), _) => date,
+            (None, Some(err)) => return Err(err),
+            (None, None) => return Err(ApiError::InvalidArguments {
+                reason: "no rate providers are configured".to_string(),
+                source: None,
+            }),
+        };
+        let rates = quotes.into_iter()
+            .map(|(code, quotes)| {
+                let rate = self.consensus(&base, &code, quotes);
+                (code, rate)
+            })
+            .collect();
+        Ok(Consensus { base, date, aggregation: self.config.aggregation, rates, failures })
+    }
+
+    /// The function `consensus` combines the quotes of one currency and judges every source against the result.
+    fn consensus(&self, base: &str, code: &str, quotes: Vec<(String, Decimal)>) -> ConsensusRate {
+        let values: Vec<Decimal> = quotes.iter().map(|(_, rate)| *rate).collect();
+        let rate = self.config.aggregation.combine(&values);
+        let sources = quotes.into_iter()
+            .map(|(provider, quote)| {
+                let deviation = if rate.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    ((quote - rate).abs() / rate * Decimal::ONE_HUNDRED).round_dp(4)
+                };
+                let flagged = deviation > self.config.max_deviation;
+                if flagged {
+                    warn!("{} quotes {}/{} at {}, {}% off the {} of {}", provider, base, code, quote, deviation, self.config.aggregation, rate);
+                }
+                SourceQuote { provider, rate: quote, deviation, flagged }
+            })
+            .collect();
+        ConsensusRate { rate, sources }
+    }
+}
+
+impl RateProvider for ConsensusProvider {
+    fn name(&self) -> &str {
+        "consensus"
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { Ok(self.latest_consensus(args).await?.to_rates(self.name())) })
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { Ok(self.historical_consensus(args).await?.to_rates(self.name())) })
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        Box::pin(async move {
+            let from = Money::new(args.amount, &args.from);
+            let to = args.to.trim().to_ascii_uppercase();
+            let consensus = match args.date {
+                Some(date) => self.historical_consensus(HistoricalRatesArguments {
+                    date,
+                    base: from.currency.clone(),
+                    symbols: Some(vec![to.clone()]),
+                }).await?,
+                None => self.latest_consensus(RatiosListArguments { base: from.currency.clone() }).await?,
+            };
+            let rate = if from.currency == to {
+                Decimal::ONE
+            } else {
+                consensus.rates.get(&to).map(|rate| rate.rate)
+                    .ok_or_else(|| ApiError::UnknownCurrency { codes: vec![to.clone()] })?
+            };
+            Ok(Conversion {
+                provider: self.name().to_string(),
+                to: from.checked_convert(rate, &to)?.round(args.rounding),
+                from,
+                rate: Some(rate),
+                date: consensus.date,
+                cache: CacheInfo::default(),
+            })
+        })
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        Box::pin(async move {
+            let answers = join_all(self.providers.iter().map(|provider| provider.currencies())).await;
+            let mut currencies: BTreeMap<String, CurrencyInfo> = BTreeMap::new();
+            let mut last_err = None;
+            let mut answered = false;
+            for answer in answers {
+                match answer {
+                    Ok(list) => {
+                        answered = true;
+                        for currency in list.currencies {
+                            let known = currencies.entry(currency.code.clone()).or_insert_with(|| currency.clone());
+                            if known.name.is_none() {
+                                known.name = currency.name;
+                            }
+                        }
+                    }
+                    Err(err) => last_err = Some(err),
+                }
+            }
+            match (answered, last_err) {
+                (false, Some(err)) => Err(err),
+                _ => Ok(Currencies {
+                    provider: self.name().to_string(),
+                    currencies: currencies.into_values().collect(),
+                    cache: CacheInfo::default(),
+                }),
+            }
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Environment variable holding the API url.
+pub const API_URL_VAR: &str = "API_URL";
+/// Environment variable holding the API key.
+pub const API_KEY_VAR: &str = "API_KEY";
+
+/// Where and how to reach the exchange rate API.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub url: String,
+    pub api_key: String,
+}
+
+/// Settings read from `config.toml`. Both keys are optional so a file can provide
+/// only one of them and leave the other to the environment.
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    api_url: Option<String>,
+    api_key: Option<String>,
+}
+
+/// Why `Credentials` could not be loaded.
+#[derive(Debug)]
+pub enum CredentialsError {
+    /// A setting was found in none of the sources that were consulted.
+    Missing {
+        setting: &'static str,
+        config_file: Option<PathBuf>,
+    },
+    /// The config file exists but could not be read.
+    ConfigFileUnreadable {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// The config file is not valid TOML or has values of the wrong type.
+    ConfigFileInvalid {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialsError::Missing { setting, config_file } => {
+                let key = if *setting == API_URL_VAR { "api_url" } else { "api_key" };
+                write!(f, "{} is not defined: set it explicitly, export {}", setting, setting)?;
+                match config_file {
+                    Some(path) => write!(f, " or add `{}` to {}", key, path.display()),
+                    None => Ok(()),
+                }
+            }
+            CredentialsError::ConfigFileUnreadable { path, source } => {
+                write!(f, "config file {} could not be read: {}", path.display(), source)
+            }
+            CredentialsError::ConfigFileInvalid { path, source } => {
+                write!(f, "config file {} is invalid: {}", path.display(), source)
+            }
+        }
+    }
+}
+
+impl Error for CredentialsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CredentialsError::Missing { .. } => None,
+            CredentialsError::ConfigFileUnreadable { source, .. } => Some(source),
+            CredentialsError::ConfigFileInvalid { source, .. } => Some(source),
+        }
+    }
+}
+
+/// The api key never shows up in logs or panic messages.
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials").field("url", &self.url).field("api_key", &"<redacted>").finish()
+    }
+}
+
+impl Credentials {
+    /// The function `load` looks every setting up in the environment first and falls back
+    /// to the config file in the XDG config directory, see `default_config_file`.
+    ///
+    /// Returns:
+    ///
+    /// The credentials, or a `CredentialsError` naming the first setting that was found nowhere.
+    pub fn load() -> Result<Self, CredentialsError> {
+        CredentialsBuilder::default().build()
+    }
+
+    /// The function `builder` starts a `CredentialsBuilder` for callers that want to pass
+    /// some values explicitly or read a config file from a custom location.
+    pub fn builder() -> CredentialsBuilder {
+        CredentialsBuilder::default()
+    }
+
+    /// The path of `config.toml` inside the XDG config directory, usually `~/.config/task/config.toml`.
+    pub fn default_config_file() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+    }
+}
+
+/// Collects credentials from up to three sources. For every setting the precedence is:
+/// values set on the builder, then environment variables, then the config file.
+#[derive(Debug, Clone)]
+pub struct CredentialsBuilder {
+    url: Option<String>,
+    api_key: Option<String>,
+    config_file: Option<PathBuf>,
+    read_env: bool,
+    read_default_config_file: bool,
+}
+
+impl Default for CredentialsBuilder {
+    fn default() -> Self {
+        CredentialsBuilder {
+            url: None,
+            api_key: None,
+            config_file: None,
+            read_env: true,
+            read_default_config_file: true,
+        }
+    }
+}
+
+impl CredentialsBuilder {
+    /// Uses `url` regardless of the environment and the config file.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Uses `api_key` regardless of the environment and the config file.
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Reads `path` instead of the default config file. Unlike the default one, this
+    /// file has to exist.
+    pub fn config_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.config_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Skips `API_URL` and `API_KEY` from the environment.
+    pub fn ignore_env(mut self) -> Self {
+        self.read_env = false;
+        self
+    }
+
+    /// Skips the default config file. A file set through `config_file` is still read.
+    pub fn ignore_default_config_file(mut self) -> Self {
+        self.read_default_config_file = false;
+        self
+    }
+
+    /// The function `build` resolves every setting from the configured sources.
+    ///
+    /// Returns:
+    ///
+    /// The credentials, or a `CredentialsError` naming the setting that is missing or the
+    /// config file that could not be used.
+    pub fn build(self) -> Result<Credentials, CredentialsError> {
+        let (config_path, required) = match (&self.config_file, self.read_default_config_file) {
+            (Some(path), _) => (Some(path.clone()), true),
+            (None, true) => (Credentials::default_config_file(), false),
+            (None, false) => (None, false),
+        };
+        let read_env = self.read_env;
+        let from_env = |var: &str| if read_env { non_empty(env::var(var).ok()) } else { None };
+        let mut url = non_empty(self.url).or_else(|| from_env(API_URL_VAR));
+        let mut api_key = non_empty(self.api_key).or_else(|| from_env(API_KEY_VAR));
+        if url.is_none() || api_key.is_none() {
+            if let Some(path) = &config_path {
+                let file = read_config_file(path, required)?;
+                url = url.or_else(|| non_empty(file.api_url));
+                api_key = api_key.or_else(|| non_empty(file.api_key));
+            }
+        }
+        let missing = |setting: &'static str| CredentialsError::Missing { setting, config_file: config_path.clone() };
+        Ok(Credentials {
+            url: url.ok_or_else(|| missing(API_URL_VAR))?,
+            api_key: api_key.ok_or_else(|| missing(API_KEY_VAR))?,
+        })
+    }
+}
+
+/// An empty value counts as not set, the same way an empty `export API_KEY=` would.
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|value| !value.trim().is_empty())
+}
+
+/// The function `read_config_file` parses `path`, treating a missing file as empty unless it is `required`.
+fn read_config_file(path: &Path, required: bool) -> Result<ConfigFile, CredentialsError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => toml::from_str(&contents)
+            .map_err(|source| CredentialsError::ConfigFileInvalid { path: path.to_path_buf(), source }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !required => Ok(ConfigFile::default()),
+        Err(source) => Err(CredentialsError::ConfigFileUnreadable { path: path.to_path_buf(), source }),
+    }
+}
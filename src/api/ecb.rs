@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use super::cache::CacheInfo;
+use super::money::Money;
+use super::provider::{Conversion, Currencies, CurrencyInfo, ProviderFuture, RateProvider, Rates};
+use super::{ApiError, Result};
+
+/// Every ECB reference rate is quoted against the euro.
+const EUR: &str = "EUR";
+
+/// Why ECB reference rates could not be loaded.
+#[derive(Debug)]
+pub enum EcbError {
+    /// A file or directory could not be read.
+    Unreadable {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// A file is not well formed XML.
+    InvalidXml {
+        path: Option<PathBuf>,
+        source: roxmltree::Error,
+    },
+    /// A `Cube` element has a date or rate that cannot be read.
+    InvalidCube {
+        path: Option<PathBuf>,
+        reason: String,
+    },
+    /// Nothing that was read held a single day of rates.
+    NoRates {
+        path: Option<PathBuf>,
+    },
+}
+
+impl EcbError {
+    fn at(self, file: &Path) -> Self {
+        let path = Some(file.to_path_buf());
+        match self {
+            EcbError::InvalidXml { source, .. } => EcbError::InvalidXml { path, source },
+            EcbError::InvalidCube { reason, .. } => EcbError::InvalidCube { path, reason },
+            EcbError::NoRates { .. } => EcbError::NoRates { path },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for EcbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let origin = |path: &Option<PathBuf>| match path {
+            Some(path) => format!(" in {}", path.display()),
+            None => String::new(),
+        };
+        match self {
+            EcbError::Unreadable { path, source } => write!(f, "ECB rates at {} could not be read: {}", path.display(), source),
+            EcbError::InvalidXml { path, source } => write!(f, "ECB rates{} are not valid XML: {}", origin(path), source),
+            EcbError::InvalidCube { path, reason } => write!(f, "ECB rates{} are invalid: {}", origin(path), reason),
+            EcbError::NoRates { path } => write!(f, "no ECB rates found{}", origin(path)),
+        }
+    }
+}
+
+impl Error for EcbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EcbError::Unreadable { source, .. } => Some(source),
+            EcbError::InvalidXml { source, .. } => Some(source),
+            EcbError::InvalidCube { .. } | EcbError::NoRates { .. } => None,
+        }
+    }
+}
+
+/// ECB euro foreign exchange reference rates read from `eurofxref` XML files, so rates are
+/// available without network access. Both the daily file (`eurofxref-daily.xml`) and the
+/// historical ones (`eurofxref-hist.xml`, `eurofxref-hist-90d.xml`) are understood.
+///
+/// Rates against any other base are crossed through EUR. The ECB publishes no rates on
+/// weekends and TARGET holidays, so a historical day falls back to the last day before it.
+#[derive(Debug, Clone)]
+pub struct EcbProvider {
+    /// Rates per day, each as the price of one euro.
+    days: BTreeMap<NaiveDate, BTreeMap<String, Decimal>>,
+}
+
+impl EcbProvider {
+    /// The function `from_xml` reads the rates of a single `eurofxref` document.
+    ///
+    /// Returns:
+    ///
+    /// The provider, or an `EcbError` when the document is not valid or holds no rates.
+    pub fn from_xml(xml: &str) -> std::result::Result<Self, EcbError> {
+        let mut days = BTreeMap::new();
+        parse_into(xml, &mut days)?;
+        if days.is_empty() {
+            return Err(EcbError::NoRates { path: None });
+        }
+        Ok(EcbProvider { days })
+    }
+
+    /// The function `open` reads the rates of `path`, which is either a single XML file or a
+    /// directory whose `*.xml` files are all read. Where files have the same day, the one that
+    /// sorts last by name wins.
+    ///
+    /// Returns:
+    ///
+    /// The provider, or an `EcbError` naming the file that could not be read.
+    pub fn open(path: impl AsRef<Path>) -> std::result::Result<Self, EcbError> {
+        let path = path.as_ref();
+        let unreadable = |source| EcbError::Unreadable { path: path.to_path_buf(), source };
+        let files: Vec<PathBuf> = if path.is_dir() {
+            let mut files = Vec::new();
+            for entry in fs::read_dir(path).map_err(unreadable)? {
+                let file = entry.map_err(unreadable)?.path();
+                let xml = matches!(file.extension().and_then(|extension| extension.to_str()),
+                    Some(extension) if extension.eq_ignore_ascii_case("xml"));
+                if xml && file.is_file() {
+                    files.push(file);
+                }
+            }
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+        let mut days = BTreeMap::new();
+        for file in files.iter() {
+            let xml = fs::read_to_string(file).map_err(|source| EcbError::Unreadable { path: file.clone(), source })?;
+            parse_into(&xml, &mut days).map_err(|err| err.at(file))?;
+        }
+        if days.is_empty() {
+            return Err(EcbError::NoRates { path: Some(path.to_path_buf()) });
+        }
+        Ok(EcbProvider { days })
+    }
+
+    /// The first and the last day there are rates for.
+    pub fn range(&self) -> (NaiveDate, NaiveDate) {
+        let first = self.days.keys().next().copied().unwrap_or_default();
+        let last = self.days.keys().next_back().copied().unwrap_or_default();
+        (first, last)
+    }
+
+    /// The function `day` finds the rates effective on `date`: those of that day or, when the
+    /// ECB published none on it, of the last day before it. `None` looks up the last day.
+    fn day(&self, date: Option<NaiveDate>) -> Result<(NaiveDate, &BTreeMap<String, Decimal>)> {
+        let found = match date {
+            Some(date) => self.days.range(..=date).next_back(),
+            None => self.days.iter().next_back(),
+        };
+        let (first, last) = self.range();
+        found.map(|(day, rates)| (*day, rates)).ok_or_else(|| ApiError::InvalidArguments {
+            reason: format!("no ECB rates on or before {}, the files cover {} to {}", date.unwrap_or(first), first, last),
+            source: None,
+        })
+    }
+
+    /// The function `rebase` turns the rates of one day into rates against `base`. The base
+    /// itself is left out, EUR is added when it is not the base.
+    fn rebase(rates: &BTreeMap<String, Decimal>, base: &str) -> Result<BTreeMap<String, Decimal>> {
+        let per_euro = |code: &str| if code == EUR { Some(Decimal::ONE) } else { rates.get(code).copied() };
+        let base_per_euro = per_euro(base).ok_or_else(|| ApiError::UnknownCurrency { codes: vec![base.to_string()] })?;
+        Ok(rates.keys().map(String::as_str)
+            .chain(std::iter::once(EUR))
+            .filter(|code| *code != base)
+            .filter_map(|code| per_euro(code).map(|rate| (code.to_string(), rate / base_per_euro)))
+            .collect())
+    }
+
+    fn rates(&self, base: &str, date: Option<NaiveDate>, symbols: Option<&[String]>) -> Result<Rates> {
+        let base = base.trim().to_ascii_uppercase();
+        let (day, rates) = self.day(date)?;
+        let mut rates = Self::rebase(rates, &base)?;
+        if let Some(symbols) = symbols {
+            let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.trim().to_ascii_uppercase()).collect();
+            let unknown: Vec<String> = symbols.iter()
+                .filter(|symbol| **symbol != base && !rates.contains_key(*symbol))
+                .cloned()
+                .collect();
+            if !unknown.is_empty() {
+                return Err(ApiError::UnknownCurrency { codes: unknown });
+            }
+            rates.retain(|code, _| symbols.contains(code));
+        }
+        Ok(Rates { provider: self.name().to_string(), base, date: day, rates, cache: CacheInfo::default() })
+    }
+}
+
+impl RateProvider for EcbProvider {
+    fn name(&self) -> &str {
+        "ecb"
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.rates(&args.base, None, None) })
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.rates(&args.base, Some(args.date), args.symbols.as_deref()) })
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        Box::pin(async move {
+            let from = Money::new(args.amount, &args.from);
+            let to = args.to.trim().to_ascii_uppercase();
+            let (day, rates) = self.day(args.date)?;
+            let known = |code: &str| code == EUR || rates.contains_key(code);
+            let unknown: Vec<String> = [&from.currency, &to].into_iter().filter(|code| !known(code)).cloned().collect();
+            if !unknown.is_empty() {
+                return Err(ApiError::UnknownCurrency { codes: unknown });
+            }
+            let rate = if from.currency == to { Decimal::ONE } else { Self::rebase(rates, &from.currency)?[&to] };
+            Ok(Conversion {
+                provider: self.name().to_string(),
+                to: from.checked_convert(rate, &to)?.round(args.rounding),
+                from,
+                rate: Some(rate),
+                date: day,
+                cache: CacheInfo::default(),
+            })
+        })
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        Box::pin(async move {
+            let codes: BTreeSet<&str> = self.days.values()
+                .flat_map(|rates| rates.keys().map(String::as_str))
+                .chain(std::iter::once(EUR))
+                .collect();
+            let currencies = codes.into_iter()
+                .map(|code| CurrencyInfo { code: code.to_string(), name: None })
+                .collect();
+            Ok(Currencies { provider: self.name().to_string(), currencies, cache: CacheInfo::default() })
+        })
+    }
+}
+
+/// The function `parse_into` adds every `<Cube time="..">` of `xml` to `days`, each holding
+/// `<Cube currency=".." rate=".."/>` children.
+fn parse_into(xml: &str, days: &mut BTreeMap<NaiveDate, BTreeMap<String, Decimal>>) -> std::result::Result<(), EcbError> {
+    let document = roxmltree::Document::parse(xml).map_err(|source| EcbError::InvalidXml { path: None, source })?;
+    let invalid = |reason: String| EcbError::InvalidCube { path: None, reason };
+    let cubes = document.descendants().filter(|node| node.has_tag_name("Cube"));
+    for day in cubes.filter(|node| node.has_attribute("time")) {
+        let time = day.attribute("time").unwrap_or_default();
+        let date = NaiveDate::parse_from_str(time, "%Y-%m-%d")
+            .map_err(|err| invalid(format!("`{}` is not a date: {}", time, err)))?;
+        let mut rates = BTreeMap::new();
+        for cube in day.children().filter(|node| node.has_tag_name("Cube")) {
+            let (Some(currency), Some(rate)) = (cube.attribute("currency"), cube.attribute("rate")) else {
+                continue;
+            };
+            let rate = Decimal::from_str(rate.trim())
+                .map_err(|err| invalid(format!("rate `{}` of {} on {} is not a number: {}", rate, currency, date, err)))?;
+            if rate.is_zero() || rate.is_sign_negative() {
+                return Err(invalid(format!("rate of {} on {} is not positive", currency, date)));
+            }
+            rates.insert(currency.trim().to_ascii_uppercase(), rate);
+        }
+        days.insert(date, rates);
+    }
+    Ok(())
+}
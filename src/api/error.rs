@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use super::models::Meta;
+
+/// Everything that can go wrong while talking to the exchange rate API.
+///
+/// Variants keep the error they were built from, so callers can match on the cause
+/// and decide whether to retry, report or give up.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request never got a response: DNS, connect, TLS, timeout or a dropped connection.
+    Transport(reqwest::Error),
+    /// The API answered with a non-success status that has no more specific variant.
+    Status {
+        status: StatusCode,
+        detail: Option<String>,
+        source: Option<reqwest::Error>,
+    },
+    /// The API key was missing, wrong or revoked.
+    Unauthorized {
+        status: StatusCode,
+        detail: Option<String>,
+        source: Option<reqwest::Error>,
+    },
+    /// The plan quota or rate limit has been used up.
+    QuotaExceeded {
+        detail: Option<String>,
+        /// How long the API asked to wait through `Retry-After`, if it did.
+        retry_after: Option<Duration>,
+        source: Option<reqwest::Error>,
+    },
+    /// The client side rate limiter had no token left and was told not to wait for one.
+    /// Nothing was sent; `wait` is how long until the next token is available.
+    Throttled {
+        wait: Duration,
+    },
+    /// One of the given currency codes is not known to the API.
+    UnknownCurrency {
+        codes: Vec<String>,
+    },
+    /// The body could not be decoded into the expected shape.
+    MalformedPayload {
+        body: String,
+        source: serde_json::Error,
+    },
+    /// The request could not be built from the given arguments.
+    InvalidArguments {
+        reason: String,
+        source: Option<Box<dyn Error + Send + Sync>>,
+    },
+}
+
+impl ApiError {
+    /// The function `invalid_arguments` builds an `InvalidArguments` error that keeps the
+    /// error it was caused by.
+    pub fn invalid_arguments<E>(reason: impl Into<String>, source: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        ApiError::InvalidArguments { reason: reason.into(), source: Some(Box::new(source)) }
+    }
+
+    /// The function `from_response` turns a raw HTTP answer into an error when either the
+    /// HTTP status or the `meta.code` of the payload reports a failure.
+    ///
+    /// Returns:
+    ///
+    /// `None` when the response is a success, otherwise the matching `ApiError` variant.
+    pub fn from_response(status: StatusCode, body: &str, source: Option<reqwest::Error>) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            meta: Meta,
+        }
+        let meta: Option<Meta> = serde_json::from_str::<ErrorBody>(body).ok().map(|error| error.meta);
+        let status = match &meta {
+            Some(meta) if status.is_success() && meta.code >= 400 => {
+                StatusCode::from_u16(meta.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            _ => status,
+        };
+        if status.is_success() {
+            return None;
+        }
+        let detail = meta.and_then(|meta| meta.error_detail.or(meta.error_type));
+        Some(match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ApiError::Unauthorized { status, detail, source },
+            StatusCode::TOO_MANY_REQUESTS => ApiError::QuotaExceeded { detail, retry_after: None, source },
+            _ => ApiError::Status { status, detail, source },
+        })
+    }
+
+    /// The function `with_retry_after` attaches the `Retry-After` of the response to a
+    /// `QuotaExceeded` error and leaves every other error as it is.
+    pub fn with_retry_after(self, retry_after: Option<Duration>) -> Self {
+        match self {
+            ApiError::QuotaExceeded { detail, source, .. } => ApiError::QuotaExceeded { detail, retry_after, source },
+            other => other,
+        }
+    }
+
+    /// The HTTP status behind the error, if the API answered at all.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Transport(err) => err.status(),
+            ApiError::Status { status, .. } | ApiError::Unauthorized { status, .. } => Some(*status),
+            ApiError::QuotaExceeded { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Transport(err) => write!(f, "request could not be sent: {}", err),
+            ApiError::Status { status, detail, .. } => match detail {
+                Some(detail) => write!(f, "API answered with {}: {}", status, detail),
+                None => write!(f, "API answered with {}", status),
+            },
+            ApiError::Unauthorized { detail, .. } => match detail {
+                Some(detail) => write!(f, "API key was rejected: {}", detail),
+                None => write!(f, "API key was rejected"),
+            },
+            ApiError::QuotaExceeded { detail, .. } => match detail {
+                Some(detail) => write!(f, "API quota exceeded: {}", detail),
+                None => write!(f, "API quota exceeded"),
+            },
+            ApiError::Throttled { wait } => write!(f, "client rate limit reached, next request possible in {:?}", wait),
+            ApiError::UnknownCurrency { codes } => write!(f, "unknown currency: {}", codes.join(" or ")),
+            ApiError::MalformedPayload { source, .. } => write!(f, "API payload could not be decoded: {}", source),
+            ApiError::InvalidArguments { reason, .. } => write!(f, "invalid arguments: {}", reason),
+        }
+    }
+}
+
+impl Error for ApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ApiError::Transport(err) => Some(err),
+            ApiError::Status { source, .. }
+            | ApiError::Unauthorized { source, .. }
+            | ApiError::QuotaExceeded { source, .. } => source.as_ref().map(|err| err as &(dyn Error + 'static)),
+            ApiError::Throttled { .. } | ApiError::UnknownCurrency { .. } => None,
+            ApiError::MalformedPayload { source, .. } => Some(source),
+            ApiError::InvalidArguments { source, .. } => source.as_deref().map(|err| err as &(dyn Error + 'static)),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        ApiError::Transport(err)
+    }
+}
@@ -0,0 +1,203 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use serde::Serialize;
+
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use super::provider::{Conversion, Currencies, ProviderFuture, RateProvider, Rates};
+use super::{ApiError, Result};
+
+/// When a provider of a `FailoverProvider` is taken out of rotation and when it is tried again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// Failures in a row after which a provider is marked unhealthy.
+    pub failure_threshold: u32,
+    /// How long an unhealthy provider is passed over before it is probed with a real request again.
+    pub probe_after: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        HealthPolicy { failure_threshold: 3, probe_after: Duration::from_secs(30) }
+    }
+}
+
+/// How a provider of a `FailoverProvider` is doing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub healthy: bool,
+    /// Failures since the last success.
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct HealthState {
+    consecutive_failures: u32,
+    /// When the provider was marked unhealthy or last failed a probe.
+    unhealthy_since: Option<Instant>,
+}
+
+struct Member {
+    provider: Box<dyn RateProvider>,
+    health: Mutex<HealthState>,
+}
+
+impl Member {
+    fn health(&self) -> MutexGuard<'_, HealthState> {
+        match self.health.lock() {
+            Ok(health) => health,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Whether the provider is healthy, or unhealthy long enough to be probed again.
+    fn ready(&self, policy: &HealthPolicy, now: Instant) -> bool {
+        match self.health().unhealthy_since {
+            Some(since) => now.duration_since(since) >= policy.probe_after,
+            None => true,
+        }
+    }
+
+    fn succeeded(&self) {
+        let mut health = self.health();
+        if health.unhealthy_since.is_some() {
+            info!("Rate provider {} answered again and is healthy", self.provider.name());
+        }
+        *health = HealthState::default();
+    }
+
+    fn failed(&self, policy: &HealthPolicy, err: &ApiError) {
+        let mut health = self.health();
+        health.consecutive_failures += 1;
+        if health.unhealthy_since.is_some() {
+            health.unhealthy_since = Some(Instant::now());
+            debug!("Probe of rate provider {} failed: {}", self.provider.name(), err);
+        } else if health.consecutive_failures >= policy.failure_threshold {
+            health.unhealthy_since = Some(Instant::now());
+            warn!(
+                "Rate provider {} failed {} times in a row and is unhealthy, probing again in {:?}: {}",
+                self.provider.name(), health.consecutive_failures, policy.probe_after, err,
+            );
+        }
+    }
+}
+
+/// Tries an ordered list of providers until one answers, e.g. CurrencyBeacon, then a
+/// `SnapshotProvider`, then an `EcbProvider`. The `provider` of every result names the one
+/// that actually answered.
+///
+/// Providers that fail `HealthPolicy::failure_threshold` times in a row are marked unhealthy
+/// and moved behind the healthy ones until `HealthPolicy::probe_after` has passed, when the next
+/// request probes them in their usual place again. Only failures of the provider itself count:
+/// an unknown currency or a date a provider has no rates for moves on to the next provider
+/// without hurting its health.
+pub struct FailoverProvider {
+    members: Vec<Member>,
+    policy: HealthPolicy,
+}
+
+impl FailoverProvider {
+    /// The function `new` chains `providers` in the order they are tried, with the default `HealthPolicy`.
+    pub fn new(providers: Vec<Box<dyn RateProvider>>) -> Self {
+        let members = providers.into_iter()
+            .map(|provider| Member { provider, health: Mutex::new(HealthState::default()) })
+            .collect();
+        FailoverProvider { members, policy: HealthPolicy::default() }
+    }
+
+    /// The function `with_policy` replaces the `HealthPolicy` the providers are judged by.
+    pub fn with_policy(mut self, policy: HealthPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The function `health` reports how every provider is doing, in the order they are tried.
+    pub fn health(&self) -> Vec<ProviderHealth> {
+        self.members.iter()
+            .map(|member| {
+                let health = member.health();
+                ProviderHealth {
+                    provider: member.provider.name().to_string(),
+                    healthy: health.unhealthy_since.is_none(),
+                    consecutive_failures: health.consecutive_failures,
+                }
+            })
+            .collect()
+    }
+
+    /// The function `first_answer` runs `call` against the providers until one succeeds.
+    ///
+    /// Returns:
+    ///
+    /// The first successful answer, or the most telling error of the providers tried: an unknown
+    /// currency, else a failure of a provider itself, else that a provider could not answer, the
+    /// earliest of them when several are alike. So a fallback without rates, like an empty
+    /// snapshot, does not hide why the providers before it failed.
+    async fn first_answer<'a, T>(&'a self, call: impl Fn(&'a dyn RateProvider) -> ProviderFuture<'a, T>) -> Result<T> {
+        let now = Instant::now();
+        let (ready, resting): (Vec<&Member>, Vec<&Member>) = self.members.iter()
+            .partition(|member| member.ready(&self.policy, now));
+        let mut best_err: Option<ApiError> = None;
+        for member in ready.into_iter().chain(resting) {
+            match call(member.provider.as_ref()).await {
+                Ok(answer) => {
+                    member.succeeded();
+                    return Ok(answer);
+                }
+                Err(err) => {
+                    debug!("Rate provider {} failed, trying the next one: {}", member.provider.name(), err);
+                    if counts_against_health(&err) {
+                        member.failed(&self.policy, &err);
+                    }
+                    best_err = match best_err {
+                        Some(best) if relevance(&best) <= relevance(&err) => Some(best),
+                        _ => Some(err),
+                    };
+                }
+            }
+        }
+        Err(best_err.unwrap_or_else(|| ApiError::InvalidArguments {
+            reason: "no rate providers are configured".to_string(),
+            source: None,
+        }))
+    }
+}
+
+/// How much `err` tells the caller, lower is more: that a currency does not exist, then that a
+/// provider is in trouble, then that a provider cannot answer this request, e.g. having no rates.
+fn relevance(err: &ApiError) -> u8 {
+    match err {
+        ApiError::UnknownCurrency { .. } => 0,
+        ApiError::InvalidArguments { .. } => 2,
+        _ => 1,
+    }
+}
+
+/// Whether `err` says the provider is in trouble, rather than that it cannot answer this request.
+fn counts_against_health(err: &ApiError) -> bool {
+    !matches!(err, ApiError::UnknownCurrency { .. } | ApiError::InvalidArguments { .. })
+}
+
+impl RateProvider for FailoverProvider {
+    fn name(&self) -> &str {
+        "failover"
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(self.first_answer(move |provider| provider.latest(args.clone())))
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(self.first_answer(move |provider| provider.historical(args.clone())))
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        Box::pin(self.first_answer(move |provider| provider.convert(args.clone())))
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        Box::pin(self.first_answer(|provider| provider.currencies()))
+    }
+}
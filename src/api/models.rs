@@ -0,0 +1,140 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use super::cache::CacheInfo;
+use super::money::Money;
+
+/// Every CurrencyBeacon payload carries its data in `response`, next to a `meta` block.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Envelope<T> {
+    pub meta: Meta,
+    pub response: T,
+}
+
+/// The `meta` block of a CurrencyBeacon payload.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Meta {
+    pub code: u16,
+    #[serde(default)]
+    pub disclaimer: Option<String>,
+    #[serde(default)]
+    pub error_type: Option<String>,
+    #[serde(default)]
+    pub error_detail: Option<String>,
+}
+
+/// Result of `/v1/convert`. `value` is `None` when the API answered with an empty
+/// array, which is how CurrencyBeacon reports a currency it does not know.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConvertResponse {
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(default)]
+    pub date: String,
+    pub from: String,
+    pub to: String,
+    pub amount: Decimal,
+    #[serde(default, deserialize_with = "empty_array_as_default")]
+    pub value: Option<Decimal>,
+    /// Whether the response was served from the cache, and how old it is.
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+impl ConvertResponse {
+    /// The converted amount in the target currency, unrounded.
+    pub fn converted(&self) -> Option<Money> {
+        self.value.map(|value| Money::new(value, &self.to))
+    }
+}
+
+/// Result of `/v1/latest`. `rates` is empty when the base currency is unknown.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LatestResponse {
+    #[serde(default)]
+    pub date: String,
+    pub base: String,
+    #[serde(default, deserialize_with = "empty_array_as_default")]
+    pub rates: BTreeMap<String, Decimal>,
+    /// Whether the response was served from the cache, and how old it is.
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+/// Result of `/v1/historical`. `date` is the day the rates are effective for, which the
+/// API reports back even when it differs from the one asked for.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HistoricalResponse {
+    pub date: NaiveDate,
+    pub base: String,
+    #[serde(default, deserialize_with = "empty_array_as_default")]
+    pub rates: BTreeMap<String, Decimal>,
+    /// Whether the response was served from the cache, and how old it is.
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+/// Result of `/v1/timeseries`: the rates against `base` for every day between `start_date`
+/// and `end_date`, ordered by date. Days the API had no rates for are left out.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TimeSeriesResponse {
+    pub base: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub rates: BTreeMap<NaiveDate, BTreeMap<String, Decimal>>,
+    /// Whether the response was served from the cache, and how old it is.
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+/// A single entry of `/v1/currencies`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Currency {
+    #[serde(default)]
+    pub id: u32,
+    pub name: String,
+    pub short_code: String,
+    #[serde(default)]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub precision: u32,
+    #[serde(default)]
+    pub subunit: u32,
+    #[serde(default)]
+    pub symbol: String,
+    #[serde(default)]
+    pub symbol_first: bool,
+    #[serde(default)]
+    pub decimal_mark: String,
+    #[serde(default)]
+    pub thousands_separator: String,
+}
+
+/// Result of `/v1/currencies`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct CurrencyList {
+    pub currencies: Vec<Currency>,
+    /// Whether the response was served from the cache, and how old it is.
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+/// Outcome of pinging the API root.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Health {
+    pub reachable: bool,
+    pub status: Option<u16>,
+}
+
+/// CurrencyBeacon answers with `[]` instead of `null` or an empty object when it has
+/// nothing to return, so this maps an empty array onto the field's default value. The field is
+/// read through a `serde_json::Value`, which keeps every digit of a number, so decimals parsed
+/// from it are exact.
+pub(crate) fn empty_array_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(items) if items.is_empty() => Ok(T::default()),
+        value => serde_json::from_value(value).map_err(serde::de::Error::custom),
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use super::ApiError;
+
+/// An exact amount in a given currency. Amounts are decimals, never floats, so invoice
+/// sized values keep every cent through parsing, conversion and display.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct Money {
+    pub amount: Decimal,
+    /// ISO 4217 code, always upper case.
+    pub currency: String,
+}
+
+impl Money {
+    /// The function `new` creates an amount of `currency`, upper casing the currency code.
+    pub fn new(amount: Decimal, currency: impl AsRef<str>) -> Self {
+        Money { amount, currency: currency.as_ref().trim().to_ascii_uppercase() }
+    }
+
+    /// The function `checked_convert` multiplies the amount by `rate`, the price of one unit of
+    /// this currency in `currency`, without panicking when the product does not fit into a
+    /// `Decimal`. Nothing is rounded; call `rounded` when the result is final.
+    ///
+    /// Returns:
+    ///
+    /// The unrounded amount of `currency`, or `ApiError::InvalidArguments` when the amount is too large.
+    pub fn checked_convert(&self, rate: Decimal, currency: impl AsRef<str>) -> super::Result<Money> {
+        let amount = self.amount.checked_mul(rate)
+            .ok_or_else(|| ApiError::InvalidArguments { reason: "amount too large".to_string(), source: None })?;
+        Ok(Money::new(amount, currency))
+    }
+
+    /// The function `rounded` rounds the amount to `places` decimal places, half away from
+    /// zero, the way amounts are usually rounded for display.
+    pub fn rounded(&self, places: u32) -> Money {
+        self.round(RoundingPolicy { mode: RoundingMode::HalfUp, places: Some(places) })
+    }
+
+    /// The function `round` rounds the amount as `policy` says. Without explicit places the
+    /// amount is rounded to the minor units of its own currency, see `minor_units`.
+    pub fn round(&self, policy: RoundingPolicy) -> Money {
+        Money {
+            amount: policy.apply(self.amount, &self.currency),
+            currency: self.currency.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.currency)
+    }
+}
+
+/// How a value that does not fit into the wanted number of decimal places is rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RoundingMode {
+    /// Ties go to the even neighbour, also known as banker's rounding: 2.345 -> 2.34.
+    HalfEven,
+    /// Ties go away from zero: 2.345 -> 2.35, -2.345 -> -2.35.
+    #[default]
+    HalfUp,
+    /// Always towards negative infinity: 2.349 -> 2.34, -2.341 -> -2.35.
+    Floor,
+    /// Always towards positive infinity: 2.341 -> 2.35, -2.349 -> -2.34.
+    Ceil,
+    /// Drops the extra digits, towards zero: 2.349 -> 2.34, -2.349 -> -2.34.
+    Truncate,
+}
+
+impl RoundingMode {
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::Floor => RoundingStrategy::ToNegativeInfinity,
+            RoundingMode::Ceil => RoundingStrategy::ToPositiveInfinity,
+            RoundingMode::Truncate => RoundingStrategy::ToZero,
+        }
+    }
+}
+
+impl fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RoundingMode::HalfEven => "half-even",
+            RoundingMode::HalfUp => "half-up",
+            RoundingMode::Floor => "floor",
+            RoundingMode::Ceil => "ceil",
+            RoundingMode::Truncate => "truncate",
+        })
+    }
+}
+
+impl FromStr for RoundingMode {
+    type Err = String;
+
+    /// Accepts the names printed by `Display`, case insensitive, plus `bankers` for `HalfEven`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "half-even" | "bankers" => Ok(RoundingMode::HalfEven),
+            "half-up" => Ok(RoundingMode::HalfUp),
+            "floor" => Ok(RoundingMode::Floor),
+            "ceil" => Ok(RoundingMode::Ceil),
+            "truncate" => Ok(RoundingMode::Truncate),
+            other => Err(format!("unknown rounding mode '{}', expected half-even, half-up, floor, ceil or truncate", other)),
+        }
+    }
+}
+
+/// The rounding mode together with the number of decimal places to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+pub struct RoundingPolicy {
+    pub mode: RoundingMode,
+    /// `None` rounds to the minor units of the currency the amount is in.
+    pub places: Option<u32>,
+}
+
+impl RoundingPolicy {
+    /// The function `apply` rounds `amount`, an amount of `currency`, as the policy says.
+    pub fn apply(&self, amount: Decimal, currency: &str) -> Decimal {
+        let places = self.places.unwrap_or_else(|| minor_units(currency));
+        amount.round_dp_with_strategy(places, self.mode.strategy())
+    }
+}
+
+/// The function `minor_units` looks up how many decimal places `currency` has according to
+/// ISO 4217, e.g. 0 for JPY, 3 for KWD. Unknown codes get the usual 2.
+pub fn minor_units(currency: &str) -> u32 {
+    match currency.trim().to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX" | "UYI" | "VND"
+        | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
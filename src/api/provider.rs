@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use super::cache::CacheInfo;
+use super::money::Money;
+use super::Result;
+
+/// The future returned by every `RateProvider` operation. It is boxed so providers can be
+/// kept behind `dyn RateProvider` and swapped at runtime.
+pub type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A source of exchange rates. Application code talks to this trait instead of a concrete
+/// API, so sources can be added or swapped without touching it.
+///
+/// Errors are reported as `ApiError` whatever the source, an unknown currency always as
+/// `ApiError::UnknownCurrency`.
+pub trait RateProvider: Send + Sync {
+    /// Short name of the source, reported in every result, e.g. `currencybeacon`.
+    fn name(&self) -> &str;
+
+    /// The current rates against `args.base`.
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates>;
+
+    /// The rates against `args.base` effective on `args.date`.
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates>;
+
+    /// Converts `args.amount` from `args.from` into `args.to`, rounded by `args.rounding`.
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion>;
+
+    /// Every currency the source has rates for.
+    fn currencies(&self) -> ProviderFuture<'_, Currencies>;
+}
+
+/// Lets one provider be shared, e.g. by the application and a `FailoverProvider` at once.
+impl<P: RateProvider + ?Sized> RateProvider for std::sync::Arc<P> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        (**self).latest(args)
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        (**self).historical(args)
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        (**self).convert(args)
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        (**self).currencies()
+    }
+}
+
+/// Rates against `base`: one unit of `base` buys `rates[code]` of every listed currency.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Rates {
+    /// Name of the provider that answered.
+    pub provider: String,
+    pub base: String,
+    /// The day the rates are effective for.
+    pub date: NaiveDate,
+    pub rates: BTreeMap<String, Decimal>,
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+
+/// An amount converted into another currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Conversion {
+    /// Name of the provider that answered.
+    pub provider: String,
+    pub from: Money,
+    /// The converted amount, rounded as the arguments asked.
+    pub to: Money,
+    /// Price of one unit of `from` in the currency of `to`, unrounded. `None` when it cannot be
+    /// told from the answer, e.g. for an amount of zero.
+    pub rate: Option<Decimal>,
+    /// The day of the rate the amount was converted at.
+    pub date: NaiveDate,
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+
+/// A currency a provider has rates for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurrencyInfo {
+    /// ISO 4217 code, upper case.
+    pub code: String,
+    /// Display name, when the provider has one.
+    pub name: Option<String>,
+}
+
+/// All currencies of a provider, ordered by code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Currencies {
+    /// Name of the provider that answered.
+    pub provider: String,
+    pub currencies: Vec<CurrencyInfo>,
+    #[serde(skip)]
+    pub cache: CacheInfo,
+}
+
+/// The function `parse_day` reads the day out of a date as providers send it, either
+/// `2024-03-09` or a timestamp such as `2024-03-09T12:00:00Z`.
+///
+/// Returns:
+///
+/// The day, or today in UTC when the provider sent none that can be read.
+pub(crate) fn parse_day(date: &str) -> NaiveDate {
+    date.get(..10)
+        .and_then(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok())
+        .unwrap_or_else(|| Utc::now().date_naive())
+}
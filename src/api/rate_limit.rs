@@ -0,0 +1,96 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use super::{ApiError, Result};
+
+/// What a request does when the limiter has no token left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LimitMode {
+    /// Sleeps until a token is refilled.
+    #[default]
+    Wait,
+    /// Returns `ApiError::Throttled` right away.
+    FailFast,
+}
+
+/// A token bucket: up to `burst` requests may be sent at once, after which one more is
+/// allowed every `refill_every`. E.g. `burst: 10, refill_every: 1s` stays under 60 a minute
+/// on average while still letting short bursts through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub refill_every: Duration,
+    pub mode: LimitMode,
+}
+
+/// The shared state of a `RateLimit`. Every clone of a client uses the same limiter, so the
+/// limit holds for all requests of the process that go through them.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_every: Duration,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// The function `new` creates a limiter with a full bucket. A zero `burst` is treated as one.
+    pub fn new(limit: &RateLimit) -> Self {
+        let capacity = limit.burst.max(1) as f64;
+        RateLimiter {
+            capacity,
+            refill_every: limit.refill_every,
+            bucket: Mutex::new(Bucket { tokens: capacity, refilled_at: Instant::now() }),
+        }
+    }
+
+    /// The function `try_acquire` takes a token if one is available.
+    ///
+    /// Returns:
+    ///
+    /// `Ok` when a token was taken, otherwise how long it takes until the next one is refilled.
+    pub fn try_acquire(&self) -> std::result::Result<(), Duration> {
+        let mut bucket = match self.bucket.lock() {
+            Ok(bucket) => bucket,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let now = Instant::now();
+        if self.refill_every.is_zero() {
+            bucket.tokens = self.capacity;
+        } else {
+            let refilled = now.duration_since(bucket.refilled_at).as_secs_f64() / self.refill_every.as_secs_f64();
+            bucket.tokens = (bucket.tokens + refilled).min(self.capacity);
+        }
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(self.refill_every.mul_f64(1.0 - bucket.tokens))
+    }
+
+    /// The function `acquire` takes a token, waiting for one when `mode` is `LimitMode::Wait`.
+    ///
+    /// Returns:
+    ///
+    /// `Ok` once a token was taken, or `ApiError::Throttled` in `LimitMode::FailFast` when none is left.
+    pub async fn acquire(&self, mode: LimitMode) -> Result<()> {
+        loop {
+            match (self.try_acquire(), mode) {
+                (Ok(()), _) => return Ok(()),
+                (Err(wait), LimitMode::FailFast) => return Err(ApiError::Throttled { wait }),
+                (Err(wait), LimitMode::Wait) => {
+                    debug!("Rate limit reached, waiting {:?} for a token", wait);
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use super::models::LatestResponse;
+use super::money::{Money, RoundingPolicy};
+use super::provider::Rates;
+use super::{ApiError, Result};
+
+/// Identifies the `latest` response a `RateTable` was built from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Snapshot {
+    pub base: String,
+    pub date: String,
+}
+
+/// How a rate was derived from the snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum RatePath {
+    /// Both currencies are the same, the rate is 1.
+    Identity,
+    /// The snapshot lists the rate of the target against the source, which is its base.
+    Direct { from: String, to: String },
+    /// The target is the base, the rate is the inverse of the listed one.
+    Inverse { from: String, to: String },
+    /// Neither currency is the base, the rate is crossed through it.
+    Cross { from: String, via: String, to: String },
+}
+
+impl fmt::Display for RatePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RatePath::Identity => write!(f, "identity"),
+            RatePath::Direct { from, to } => write!(f, "{} -> {}", from, to),
+            RatePath::Inverse { from, to } => write!(f, "{} -> {} (inverse)", from, to),
+            RatePath::Cross { from, via, to } => write!(f, "{} -> {} -> {}", from, via, to),
+        }
+    }
+}
+
+/// A conversion computed locally by a `RateTable`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableConversion {
+    pub from: Money,
+    pub to: Money,
+    /// Price of one unit of `from` in the currency of `to`, unrounded.
+    pub rate: Decimal,
+    pub snapshot: Snapshot,
+    pub path: RatePath,
+}
+
+/// All rates of one `/v1/latest` snapshot. Converts between any two currencies it lists
+/// without calling the API again, going through the snapshot's base when needed.
+#[derive(Debug, Clone)]
+pub struct RateTable {
+    snapshot: Snapshot,
+    rates: BTreeMap<String, Decimal>,
+}
+
+impl From<LatestResponse> for RateTable {
+    fn from(latest: LatestResponse) -> Self {
+        let base = latest.base.to_ascii_uppercase();
+        let rates = latest.rates.into_iter()
+            .map(|(code, rate)| (code.to_ascii_uppercase(), rate))
+            .filter(|(_, rate)| !rate.is_zero())
+            .collect();
+        RateTable { snapshot: Snapshot { base, date: latest.date }, rates }
+    }
+}
+
+impl From<Rates> for RateTable {
+    fn from(rates: Rates) -> Self {
+        let base = rates.base.to_ascii_uppercase();
+        let rates_by_code = rates.rates.into_iter()
+            .map(|(code, rate)| (code.to_ascii_uppercase(), rate))
+            .filter(|(_, rate)| !rate.is_zero())
+            .collect();
+        RateTable { snapshot: Snapshot { base, date: rates.date.to_string() }, rates: rates_by_code }
+    }
+}
+
+impl RateTable {
+    /// The snapshot every conversion of this table is based on.
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// The function `currencies` lists every currency the table can convert between, base included.
+    pub fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self.rates.keys().cloned().collect();
+        if !self.rates.contains_key(&self.snapshot.base) {
+            currencies.push(self.snapshot.base.clone());
+            currencies.sort();
+        }
+        currencies
+    }
+
+    /// The function `rate` finds the price of one unit of `from` in `to`.
+    ///
+    /// Returns:
+    ///
+    /// The rate and the path it was derived through, or `ApiError::UnknownCurrency` listing
+    /// the currencies the snapshot does not have.
+    pub fn rate(&self, from: &str, to: &str) -> Result<(Decimal, RatePath)> {
+        let from = from.trim().to_ascii_uppercase();
+        let to = to.trim().to_ascii_uppercase();
+        let base = &self.snapshot.base;
+        let listed = |code: &String| code == base || self.rates.contains_key(code);
+        let unknown: Vec<String> = [&from, &to].into_iter().filter(|code| !listed(code)).cloned().collect();
+        if !unknown.is_empty() {
+            return Err(ApiError::UnknownCurrency { codes: unknown });
+        }
+        if from == to {
+            return Ok((Decimal::ONE, RatePath::Identity));
+        }
+        if &from == base {
+            return Ok((self.rates[&to], RatePath::Direct { from, to }));
+        }
+        if &to == base {
+            return Ok((Decimal::ONE / self.rates[&from], RatePath::Inverse { from, to }));
+        }
+        let rate = self.rates[&to] / self.rates[&from];
+        Ok((rate, RatePath::Cross { from, via: base.clone(), to }))
+    }
+
+    /// The function `convert` exchanges `amount` into `to` locally and rounds the result by `rounding`.
+    ///
+    /// Returns:
+    ///
+    /// The conversion together with the snapshot and path it used, `ApiError::UnknownCurrency`, or
+    /// `ApiError::InvalidArguments` when the amount is too large to convert.
+    pub fn convert(&self, amount: &Money, to: &str, rounding: RoundingPolicy) -> Result<TableConversion> {
+        let (rate, path) = self.rate(&amount.currency, to)?;
+        Ok(TableConversion {
+            from: amount.clone(),
+            to: amount.checked_convert(rate, to)?.round(rounding),
+            rate,
+            snapshot: self.snapshot.clone(),
+            path,
+        })
+    }
+}
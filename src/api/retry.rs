@@ -0,0 +1,109 @@
+use std::time::{Duration, SystemTime};
+
+use super::ApiError;
+
+/// The function `parse_retry_after` reads the value of a `Retry-After` header, which RFC 9110
+/// allows to be either a number of seconds or an HTTP date.
+///
+/// Returns:
+///
+/// How long to wait from `now`, zero for a date that has passed, or `None` when the value is neither.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Kinds of failures a `RetryPolicy` may retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+    /// No response at all: timeouts, refused or reset connections.
+    Transport,
+    /// 5xx answers.
+    ServerError,
+    /// 429 answers, waiting for `Retry-After` when the API sends it.
+    RateLimited,
+}
+
+impl RetryClass {
+    /// The function `of` classifies `err`, or returns `None` for errors retrying cannot fix,
+    /// such as a rejected API key, an unknown currency or a malformed payload.
+    pub fn of(err: &ApiError) -> Option<RetryClass> {
+        match err {
+            ApiError::Transport(err) if !err.is_builder() => Some(RetryClass::Transport),
+            ApiError::Status { status, .. } if status.is_server_error() => Some(RetryClass::ServerError),
+            ApiError::QuotaExceeded { .. } => Some(RetryClass::RateLimited),
+            _ => None,
+        }
+    }
+}
+
+/// When and how often a failed request is sent again. Every endpoint is a GET, so retrying
+/// never repeats a side effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, the first one included. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry; every further retry waits twice as long.
+    pub base_delay: Duration,
+    /// Upper bound of a single delay. A `Retry-After` longer than this is not waited for.
+    pub max_delay: Duration,
+    /// Fraction, between 0 and 1, by which a delay is randomly shortened so that many clients
+    /// failing together do not retry in lockstep.
+    pub jitter: f64,
+    /// Failures that are retried; anything else is returned right away.
+    pub retry_on: Vec<RetryClass>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.5,
+            retry_on: vec![RetryClass::Transport, RetryClass::ServerError, RetryClass::RateLimited],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that sends every request only once.
+    pub fn none() -> Self {
+        RetryPolicy { max_attempts: 1, ..RetryPolicy::default() }
+    }
+
+    /// The function `backoff` computes the delay before retry number `retry`, counted from 1,
+    /// without jitter: `base_delay * 2^(retry - 1)`, capped at `max_delay`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// The function `delay` decides whether `err`, raised by attempt number `attempt` (counted
+    /// from 1), is retried and how long to wait first.
+    ///
+    /// Returns:
+    ///
+    /// `None` when the error is not retryable, the attempts are used up or the API asked to
+    /// wait longer than `max_delay`; otherwise the delay, which is the `Retry-After` of a 429
+    /// when there is one and the jittered backoff when there is not.
+    pub fn delay(&self, attempt: u32, err: &ApiError) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let class = RetryClass::of(err)?;
+        if !self.retry_on.contains(&class) {
+            return None;
+        }
+        if let ApiError::QuotaExceeded { retry_after: Some(retry_after), .. } = err {
+            return (*retry_after <= self.max_delay).then_some(*retry_after);
+        }
+        let backoff = self.backoff(attempt);
+        let jitter = self.jitter.clamp(0.0, 1.0) * fastrand::f64();
+        Some(backoff.mul_f64(1.0 - jitter))
+    }
+}
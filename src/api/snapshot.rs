@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+use chrono::NaiveDate;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use super::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use super::cache::CacheInfo;
+use super::money::Money;
+use super::provider::{Conversion, Currencies, CurrencyInfo, ProviderFuture, RateProvider, Rates};
+use super::rate_table::RateTable;
+use super::{ApiError, Result};
+
+/// The snapshot as it is kept in the state file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct StoredSnapshot {
+    rates: Rates,
+    recorded_at: SystemTime,
+}
+
+/// The last known rates, kept so there is something to answer with while every live source is
+/// down. Wrap the live sources in a `RecordingProvider` to feed it every fresh answer, or call
+/// `record` by hand; with a state file the snapshot also survives restarts.
+///
+/// Answers are always marked as served from cache, with the age of the snapshot. Historical
+/// rates and dated conversions are only answered for the day of the snapshot.
+#[derive(Debug)]
+pub struct SnapshotProvider {
+    path: Option<PathBuf>,
+    snapshot: Mutex<Option<StoredSnapshot>>,
+}
+
+impl SnapshotProvider {
+    /// The function `open` loads the snapshot kept in `path`. A missing file starts without one.
+    ///
+    /// Returns:
+    ///
+    /// The provider, or the IO error raised while reading a file that exists but cannot be read
+    /// or decoded.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let snapshot = match fs::read_to_string(&path) {
+            Ok(contents) => Some(serde_json::from_str(&contents).map_err(std::io::Error::from)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        Ok(SnapshotProvider { path: Some(path), snapshot: Mutex::new(snapshot) })
+    }
+
+    /// The function `in_memory` creates a provider without a state file and without a snapshot.
+    pub fn in_memory() -> Self {
+        SnapshotProvider { path: None, snapshot: Mutex::new(None) }
+    }
+
+    /// The state file inside the XDG data directory, usually `~/.local/share/task/snapshot.json`.
+    pub fn default_state_file() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("snapshot.json"))
+    }
+
+    /// The function `record` makes `rates` the snapshot and writes it to the state file. Rates
+    /// this provider answered with itself are ignored, so the snapshot never gets younger than
+    /// it is, and so are rates of a day before the snapshot, e.g. historical ones.
+    pub fn record(&self, rates: &Rates) {
+        if rates.provider == self.name() || rates.rates.is_empty() {
+            return;
+        }
+        if matches!(self.snapshot().as_ref(), Some(stored) if stored.rates.date > rates.date) {
+            return;
+        }
+        let stored = StoredSnapshot { rates: rates.clone(), recorded_at: SystemTime::now() };
+        if let Some(path) = &self.path {
+            let result = serde_json::to_string_pretty(&stored)
+                .map_err(std::io::Error::from)
+                .and_then(|contents| {
+                    if let Some(dir) = path.parent() {
+                        fs::create_dir_all(dir)?;
+                    }
+                    fs::write(path, contents)
+                });
+            if let Err(err) = result {
+                warn!("Rate snapshot could not be written to {}: {}", path.display(), err);
+            }
+        }
+        *self.snapshot() = Some(stored);
+    }
+
+    /// The rates of the current snapshot, as they were recorded.
+    pub fn rates(&self) -> Option<Rates> {
+        self.snapshot().as_ref().map(|stored| stored.rates.clone())
+    }
+
+    fn snapshot(&self) -> MutexGuard<'_, Option<StoredSnapshot>> {
+        match self.snapshot.lock() {
+            Ok(snapshot) => snapshot,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// The function `table` builds a rate table of the snapshot, provided it is of `date` when one is given.
+    fn table(&self, date: Option<NaiveDate>) -> Result<(RateTable, Rates, CacheInfo)> {
+        let stored = self.snapshot().clone().ok_or_else(|| ApiError::InvalidArguments {
+            reason: "no rate snapshot has been recorded yet".to_string(),
+            source: None,
+        })?;
+        if let Some(date) = date {
+            if date != stored.rates.date {
+                return Err(ApiError::InvalidArguments {
+                    reason: format!("the rate snapshot is of {}, not of {}", stored.rates.date, date),
+                    source: None,
+                });
+            }
+        }
+        let age = SystemTime::now().duration_since(stored.recorded_at).unwrap_or_default();
+        let table = RateTable::from(stored.rates.clone());
+        Ok((table, stored.rates, CacheInfo { from_cache: true, age }))
+    }
+
+    fn rebased(&self, base: &str, date: Option<NaiveDate>, symbols: Option<&[String]>) -> Result<Rates> {
+        let (table, snapshot, cache) = self.table(date)?;
+        let base = base.trim().to_ascii_uppercase();
+        let wanted: Vec<String> = match symbols {
+            Some(symbols) => symbols.iter().map(|symbol| symbol.trim().to_ascii_uppercase()).collect(),
+            None => table.currencies(),
+        };
+        let mut rates = BTreeMap::new();
+        for code in wanted.into_iter().filter(|code| *code != base) {
+            let (rate, _) = table.rate(&base, &code)?;
+            rates.insert(code, rate);
+        }
+        Ok(Rates { provider: self.name().to_string(), base, date: snapshot.date, rates, cache })
+    }
+}
+
+impl RateProvider for SnapshotProvider {
+    fn name(&self) -> &str {
+        "snapshot"
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.rebased(&args.base, None, None) })
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.rebased(&args.base, Some(args.date), args.symbols.as_deref()) })
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        Box::pin(async move {
+            let (table, snapshot, cache) = self.table(args.date)?;
+            let conversion = table.convert(&Money::new(args.amount, &args.from), &args.to, args.rounding)?;
+            Ok(Conversion {
+                provider: self.name().to_string(),
+                from: conversion.from,
+                to: conversion.to,
+                rate: Some(conversion.rate),
+                date: snapshot.date,
+                cache,
+            })
+        })
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        Box::pin(async move {
+            let (table, _, cache) = self.table(None)?;
+            let currencies = table.currencies().into_iter().map(|code| CurrencyInfo { code, name: None }).collect();
+            Ok(Currencies { provider: self.name().to_string(), currencies, cache })
+        })
+    }
+}
+
+/// Feeds every `latest` and `historical` answer of `provider` to a `SnapshotProvider`, so the
+/// snapshot keeps up with a live source without its callers doing anything.
+pub struct RecordingProvider<P> {
+    provider: P,
+    snapshot: Arc<SnapshotProvider>,
+}
+
+impl<P: RateProvider> RecordingProvider<P> {
+    /// The function `new` records the rates `provider` answers with in `snapshot`.
+    pub fn new(provider: P, snapshot: Arc<SnapshotProvider>) -> Self {
+        RecordingProvider { provider, snapshot }
+    }
+}
+
+impl<P: RateProvider> RateProvider for RecordingProvider<P> {
+    fn name(&self) -> &str {
+        self.provider.name()
+    }
+
+    fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.provider.latest(args).await.inspect(|rates| self.snapshot.record(rates)) })
+    }
+
+    fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+        Box::pin(async move { self.provider.historical(args).await.inspect(|rates| self.snapshot.record(rates)) })
+    }
+
+    fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+        self.provider.convert(args)
+    }
+
+    fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+        self.provider.currencies()
+    }
+}
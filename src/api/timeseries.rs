@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use super::models::empty_array_as_default;
+
+/// Longest range, in days and both ends included, CurrencyBeacon serves from one `/v1/timeseries` call.
+pub const TIMESERIES_MAX_DAYS: u64 = 365;
+
+/// Splits the inclusive range `start..=end` into consecutive inclusive chunks of at most
+/// `max_days` days, in order. An empty range (`start > end`) yields nothing.
+#[derive(Debug, Clone)]
+pub struct DateChunks {
+    next: Option<NaiveDate>,
+    end: NaiveDate,
+    max_days: u64,
+}
+
+impl DateChunks {
+    /// The function `new` creates the iterator. A `max_days` of zero is treated as one.
+    pub fn new(start: NaiveDate, end: NaiveDate, max_days: u64) -> Self {
+        DateChunks { next: (start <= end).then_some(start), end, max_days: max_days.max(1) }
+    }
+}
+
+impl Iterator for DateChunks {
+    type Item = (NaiveDate, NaiveDate);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next?;
+        let chunk_end = start
+            .checked_add_days(Days::new(self.max_days - 1))
+            .map_or(self.end, |chunk_end| chunk_end.min(self.end));
+        self.next = chunk_end.checked_add_days(Days::new(1)).filter(|next| *next <= self.end);
+        Some((start, chunk_end))
+    }
+}
+
+/// The `response` of one `/v1/timeseries` call: rates per day, `[]` when there are none.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct TimeSeriesChunk {
+    #[serde(deserialize_with = "empty_array_as_default")]
+    pub days: BTreeMap<NaiveDate, DailyRates>,
+}
+
+/// Rates of a single day inside a `TimeSeriesChunk`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct DailyRates {
+    #[serde(deserialize_with = "empty_array_as_default")]
+    pub rates: BTreeMap<String, Decimal>,
+}
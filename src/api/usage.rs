@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{NaiveDate, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// Settings of the quota usage tracker of a `CurrencyBeaconClient`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageConfig {
+    /// Where the counts are kept between runs. `None` only counts in memory.
+    pub state_file: Option<PathBuf>,
+    /// How many requests the plan allows per calendar month, if known.
+    pub monthly_allowance: Option<u64>,
+    /// Share of `monthly_allowance`, between 0 and 1, past which a warning is logged.
+    pub warn_threshold: f64,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        UsageConfig {
+            state_file: UsageTracker::default_state_file(),
+            // The allowance of the free CurrencyBeacon plan.
+            monthly_allowance: Some(5000),
+            warn_threshold: 0.8,
+        }
+    }
+}
+
+/// The requests sent in one calendar month.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageReport {
+    /// The month as `YYYY-MM`, in UTC.
+    pub month: String,
+    /// Requests per endpoint path, e.g. `/v1/latest`.
+    pub calls: BTreeMap<String, u64>,
+    pub total: u64,
+    pub allowance: Option<u64>,
+}
+
+impl UsageReport {
+    /// The share of the allowance used so far, `None` when the allowance is unknown.
+    pub fn used_fraction(&self) -> Option<f64> {
+        self.allowance.map(|allowance| used_fraction(self.total, allowance))
+    }
+}
+
+/// How long a process waits for another one to finish writing the state file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Age past which a lock file is taken to be left behind by a process that crashed while holding it.
+const STALE_LOCK: Duration = Duration::from_secs(10);
+/// How often requests counted in memory are written to the state file.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The contents of the state file: request counts per month and endpoint.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct UsageState {
+    months: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl UsageState {
+    /// The function `add` adds the counts of `other` to these.
+    fn add(&mut self, other: &UsageState) {
+        for (month, calls) in &other.months {
+            let counts = self.months.entry(month.clone()).or_default();
+            for (endpoint, count) in calls {
+                *counts.entry(endpoint.clone()).or_default() += count;
+            }
+        }
+    }
+
+    fn total(&self, month: &str) -> u64 {
+        self.months.get(month).map_or(0, |calls| calls.values().sum())
+    }
+}
+
+/// The counts of a tracker: those last read from the state file and those not written yet.
+#[derive(Debug)]
+struct Counts {
+    stored: UsageState,
+    pending: UsageState,
+    flushed_at: Instant,
+}
+
+/// Counts outgoing requests per endpoint and calendar month, and warns once the share of the
+/// monthly allowance crosses the configured threshold. The counts are shared between runs and
+/// between processes running at the same time through the state file.
+///
+/// Recording a request only counts it in memory, so it never waits for the disk. The requests
+/// counted since the last write are added to the counts in the file by `flush`, under a lock
+/// file and with an atomic rename, when `flush_due` says so and when the tracker is dropped.
+/// A crash loses at most the requests of the last `FLUSH_INTERVAL`.
+#[derive(Debug)]
+pub struct UsageTracker {
+    config: UsageConfig,
+    counts: Mutex<Counts>,
+}
+
+impl UsageTracker {
+    /// The function `open` loads the counts kept in `config.state_file`. A missing file starts from zero.
+    ///
+    /// Returns:
+    ///
+    /// The tracker, or the IO error raised while reading a state file that exists but cannot be
+    /// read or decoded.
+    pub fn open(config: UsageConfig) -> std::io::Result<Self> {
+        let stored = match &config.state_file {
+            Some(path) => read_state(path)?,
+            None => UsageState::default(),
+        };
+        let counts = Counts { stored, pending: UsageState::default(), flushed_at: Instant::now() };
+        Ok(UsageTracker { config, counts: Mutex::new(counts) })
+    }
+
+    /// The state file inside the XDG data directory, usually `~/.local/share/task/usage.json`.
+    pub fn default_state_file() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("usage.json"))
+    }
+
+    /// The function `record` counts one request to `endpoint` in the current month.
+    pub fn record(&self, endpoint: &str) {
+        self.record_on(endpoint, Utc::now().date_naive())
+    }
+
+    /// The function `record_on` counts one request to `endpoint` in the month of `day` and logs
+    /// a warning when it is the request that crossed the threshold. Nothing is written yet.
+    pub fn record_on(&self, endpoint: &str, day: NaiveDate) {
+        let month = month_of(day);
+        let mut counts = self.counts();
+        let before = counts.stored.total(&month) + counts.pending.total(&month);
+        *counts.pending.months.entry(month.clone()).or_default().entry(endpoint.to_string()).or_default() += 1;
+        drop(counts);
+        let Some(allowance) = self.config.monthly_allowance else { return };
+        let threshold = self.config.warn_threshold;
+        let used = |total: u64| used_fraction(total, allowance);
+        if used(before + 1) >= threshold && (before == 0 || used(before) < threshold) {
+            warn!(
+                "{} of {} API requests allowed in {} are used up ({:.0}% threshold crossed)",
+                before + 1, allowance, month, threshold * 100.0,
+            );
+        }
+    }
+
+    /// The function `flush_due` tells whether requests have been counted since the last write
+    /// and `FLUSH_INTERVAL` has passed, i.e. whether it is time to call `flush`.
+    pub fn flush_due(&self) -> bool {
+        let counts = self.counts();
+        self.config.state_file.is_some() && !counts.pending.months.is_empty() && counts.flushed_at.elapsed() >= FLUSH_INTERVAL
+    }
+
+    /// The function `flush` adds the requests counted since the last write to the counts in the
+    /// state file, including those other processes wrote meanwhile. It blocks on the lock file
+    /// and the disk, so async code calls it through `tokio::task::spawn_blocking`. Counts that
+    /// cannot be written are kept for the next try.
+    pub fn flush(&self) {
+        let Some(path) = &self.config.state_file else { return };
+        let pending = {
+            let mut counts = self.counts();
+            counts.flushed_at = Instant::now();
+            std::mem::take(&mut counts.pending)
+        };
+        if pending.months.is_empty() {
+            return;
+        }
+        let written = StateLock::acquire(path).and_then(|_lock| {
+            let mut state = read_state(path)?;
+            state.add(&pending);
+            write_state(path, &state)?;
+            Ok(state)
+        });
+        let mut counts = self.counts();
+        match written {
+            Ok(state) => counts.stored = state,
+            Err(err) => {
+                warn!("API usage could not be written to {}: {}", path.display(), err);
+                counts.pending.add(&pending);
+            },
+        }
+    }
+
+    /// The function `current` reports the usage of the current month.
+    pub fn current(&self) -> UsageReport {
+        self.report(&month_of(Utc::now().date_naive()))
+    }
+
+    /// The function `report` reports the usage of `month`, given as `YYYY-MM`, as of the last
+    /// write of the state file plus the requests counted since. Months without any request are
+    /// reported with a total of zero.
+    pub fn report(&self, month: &str) -> UsageReport {
+        let counts = self.counts();
+        let mut state = UsageState::default();
+        state.add(&counts.stored);
+        state.add(&counts.pending);
+        let calls = state.months.remove(month).unwrap_or_default();
+        UsageReport {
+            month: month.to_string(),
+            total: calls.values().sum(),
+            calls,
+            allowance: self.config.monthly_allowance,
+        }
+    }
+
+    /// The function `over_threshold` tells whether the current month is past the warning threshold.
+    pub fn over_threshold(&self) -> bool {
+        match self.current().used_fraction() {
+            Some(used) => used >= self.config.warn_threshold,
+            None => false,
+        }
+    }
+
+    fn counts(&self) -> MutexGuard<'_, Counts> {
+        match self.counts.lock() {
+            Ok(counts) => counts,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Drop for UsageTracker {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// The share of `allowance` that `total` requests use; an allowance of zero is used up from the start.
+fn used_fraction(total: u64, allowance: u64) -> f64 {
+    match allowance {
+        0 => 1.0,
+        allowance => total as f64 / allowance as f64,
+    }
+}
+
+fn month_of(day: NaiveDate) -> String {
+    day.format("%Y-%m").to_string()
+}
+
+/// The path of `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn read_state(path: &Path) -> io::Result<UsageState> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(io::Error::from),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(UsageState::default()),
+        Err(err) => Err(err),
+    }
+}
+
+/// The function `write_state` writes `state` to a temporary file next to `path` and renames it
+/// over `path`, so that readers never see a half written file.
+fn write_state(path: &Path, state: &UsageState) -> io::Result<()> {
+    let contents = serde_json::to_string_pretty(state).map_err(io::Error::from)?;
+    let temporary = sibling(path, ".tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)
+}
+
+/// The lock file `<state file>.lock`, held while the state file is read and rewritten and
+/// removed when dropped.
+struct StateLock {
+    path: PathBuf,
+}
+
+impl StateLock {
+    /// The function `acquire` creates the lock file of `state_file`, waiting up to `LOCK_TIMEOUT`
+    /// while another process holds it. A lock file older than `STALE_LOCK` is removed first.
+    ///
+    /// Returns:
+    ///
+    /// The held lock, or the IO error that kept the lock file from being created.
+    fn acquire(state_file: &Path) -> io::Result<Self> {
+        if let Some(dir) = state_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let path = sibling(state_file, ".lock");
+        let started = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(StateLock { path }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path).and_then(|metadata| metadata.modified()).map(|modified| modified.elapsed().unwrap_or_default());
+                    if matches!(age, Ok(age) if age > STALE_LOCK) {
+                        warn!("Removing the stale lock {}", path.display());
+                        let _ = fs::remove_file(&path);
+                    } else if started.elapsed() > LOCK_TIMEOUT {
+                        return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} is held by another process", path.display())));
+                    } else {
+                        thread::sleep(Duration::from_millis(5));
+                    }
+                },
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
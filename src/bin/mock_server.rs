@@ -0,0 +1,107 @@
+//! Serves the CurrencyBeacon API from fixture data on localhost, see `task::mock`.
+//!
+//! ```text
+//! cargo run --features mock --bin mock_server -- --port 8080 [--fixtures fixtures/currencybeacon.json] [--api-key mock-key]
+//! cargo run --features mock --bin mock_server -- --fault 500@1 --fault latency:2000%25 --seed 7
+//! ```
+
+use std::net::SocketAddr;
+
+use clap::Parser;
+use log::{error, info};
+use task::mock::{Fault, FaultProfile, Fixtures, MockConfig, MockServer, MOCK_API_KEY};
+
+/// Serves the CurrencyBeacon API from fixture data on localhost.
+///
+/// Prints the API_URL and API_KEY to point the client at it, and serves until Ctrl+C.
+#[derive(Debug, Parser)]
+#[command(after_help = "FAULT is latency:MILLIS, 429, 429:SECONDS, a status code such as 500, truncated, html or reset.")]
+struct Args {
+    /// The port to listen on, a free one by default.
+    #[arg(long, default_value_t = 0)]
+    port: u16,
+    /// The JSON file the answers come from, the bundled fixtures/currencybeacon.json by default.
+    #[arg(long, value_name = "FILE", value_parser = fixtures)]
+    fixtures: Option<Fixtures>,
+    /// The API key requests must carry.
+    #[arg(long, value_name = "KEY", default_value = MOCK_API_KEY)]
+    api_key: String,
+    /// Fails request number N with FAULT@N, or every request with a chance of P percent with FAULT%P.
+    #[arg(long = "fault", value_name = "FAULT@N|FAULT%P", value_parser = fault)]
+    faults: Vec<FaultRule>,
+    /// Seeds the chance of the FAULT%P faults, so runs can be repeated.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+/// A `--fault` value.
+#[derive(Debug, Clone)]
+enum FaultRule {
+    OnRequest(usize, Fault),
+    Probability(f64, Fault),
+}
+
+/// The function `fault` parses a `--fault` value: `FAULT@N` fails request number `N`,
+/// `FAULT%P` fails every request with a chance of `P` percent.
+///
+/// Returns:
+///
+/// The rule, or a message telling what is wrong with the value.
+fn fault(value: &str) -> Result<FaultRule, String> {
+    if let Some((fault, request)) = value.split_once('@') {
+        let request = request.parse::<usize>().map_err(|err| format!("request number: {}", err))?;
+        return Ok(FaultRule::OnRequest(request, fault.parse::<Fault>()?));
+    }
+    if let Some((fault, percent)) = value.split_once('%') {
+        let percent = percent.parse::<f64>().map_err(|err| format!("percent: {}", err))?;
+        return Ok(FaultRule::Probability(percent / 100.0, fault.parse::<Fault>()?));
+    }
+    Err("needs @N or %P, e.g. 500@1".to_string())
+}
+
+/// The function `fixtures` reads the fixtures file at `path`.
+fn fixtures(path: &str) -> Result<Fixtures, String> {
+    Fixtures::from_file(path).map_err(|err| format!("fixtures could not be read: {}", err))
+}
+
+impl Args {
+    /// The mock settings the arguments ask for.
+    fn config(self) -> MockConfig {
+        let mut faults = self.faults.into_iter().fold(FaultProfile::none(), |faults, rule| match rule {
+            FaultRule::OnRequest(request, fault) => faults.on_request(request, fault),
+            FaultRule::Probability(probability, fault) => faults.with_probability(probability, fault),
+        });
+        if let Some(seed) = self.seed {
+            faults = faults.seeded(seed);
+        }
+        MockConfig {
+            fixtures: self.fixtures.unwrap_or_default(),
+            api_key: self.api_key,
+            addr: SocketAddr::from(([127, 0, 0, 1], self.port)),
+            faults,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let config = Args::parse().config();
+    let faults = config.faults.clone();
+    let server = match MockServer::start(config).await {
+        Ok(server) => server,
+        Err(err) => {
+            error!("Mock server could not be started: {}", err);
+            return Err(err.into());
+        }
+    };
+    let credentials = server.credentials();
+    info!("Mock CurrencyBeacon API listening on {}, stop it with Ctrl+C", server.url());
+    if !faults.is_empty() {
+        info!("Injecting faults: {:?}", faults);
+    }
+    println!("export API_URL={}", credentials.url);
+    println!("export API_KEY={}", credentials.api_key);
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
@@ -0,0 +1,203 @@
+//! The subcommands of the `task` binary, for shell scripts and cron jobs. Without a subcommand
+//! the interactive menu starts instead.
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use clap::{ArgAction, Parser, Subcommand};
+use log::{debug, info, LevelFilter};
+use rust_decimal::Decimal;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use task::api::money::{RoundingMode, RoundingPolicy};
+use task::api::snapshot::{RecordingProvider, SnapshotProvider};
+use task::api::{ApiError, CurrencyBeaconClient, RateProvider, Request, Requests};
+
+use crate::console::{self, Console};
+use crate::output::{ConversionView, CurrenciesView, HealthView, OutputFormat, Printer, RatesView, Sort, SortKey, View};
+
+/// Exchange rates and conversions from the CurrencyBeacon API.
+///
+/// Run without a subcommand for the interactive menu. Subcommands print their answer to stdout,
+/// errors to stderr, and exit with 0 on success, 2 on wrong arguments or currencies, 3 when the
+/// API answered with an error and 4 when it could not be reached.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// How results are printed.
+    #[arg(long, short, global = true, value_enum, default_value_t = OutputFormat::Plain)]
+    pub output: OutputFormat,
+    /// The column rows are sorted by.
+    #[arg(long, global = true, value_enum, default_value_t = SortKey::Code)]
+    pub sort: SortKey,
+    /// Sorts rows in descending order.
+    #[arg(long, global = true)]
+    pub desc: bool,
+    /// Logs more diagnostics to stderr: -v info, -vv debug, -vvv trace.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// Logs fewer diagnostics: -q errors only, -qq nothing.
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "verbose")]
+    pub quiet: u8,
+}
+
+impl Cli {
+    /// The printer of the chosen `--output`, `--sort` and `--desc`.
+    pub fn printer(&self) -> Printer {
+        Printer { format: self.output, sort: Sort { key: self.sort, descending: self.desc } }
+    }
+
+    /// The level of the logger chosen with `-v` and `-q`.
+    pub fn log_level(&self) -> LevelFilter {
+        console::log_level(self.verbose, self.quiet)
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Api(ApiCommand),
+    /// Opens the full-screen terminal UI: a live converter, a rates table and the API quota.
+    Tui,
+}
+
+/// The subcommands that ask CurrencyBeacon once and print the answer, run by `run`.
+#[derive(Debug, Clone, Subcommand)]
+pub enum ApiCommand {
+    /// Converts an amount into another currency, e.g. `task convert 100 USD PLN`.
+    Convert {
+        /// The amount to convert, e.g. 100 or 12.50.
+        #[arg(allow_negative_numbers = true)]
+        amount: Decimal,
+        /// The currency of the amount, e.g. USD.
+        from: String,
+        /// The currency to convert into, e.g. PLN.
+        to: String,
+        /// Converts at the rate of this day, YYYY-MM-DD, instead of the current one.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// How the result is rounded to the minor units of TO: half-up, half-even, floor, ceil or truncate.
+        #[arg(long, default_value_t = RoundingMode::default())]
+        rounding: RoundingMode,
+    },
+    /// Prints the rates against a base currency.
+    Rates {
+        /// The currency the rates are quoted against.
+        #[arg(long, default_value = "USD")]
+        base: String,
+        /// Only these currencies, comma separated, e.g. PLN,EUR.
+        #[arg(long, value_delimiter = ',')]
+        symbols: Vec<String>,
+        /// The rates effective on this day, YYYY-MM-DD, instead of the current ones.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+    },
+    /// Lists every currency the API knows.
+    Currencies,
+    /// Checks whether the API can be reached.
+    Health,
+}
+
+/// How a subcommand ended, as its process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Success = 0,
+    /// Anything not covered below, e.g. missing credentials.
+    Failure = 1,
+    /// Wrong arguments: clap rejected them, a currency does not exist or there are no rates for a date.
+    Usage = 2,
+    /// The API answered with an error: a rejected key, an exceeded quota, a server error or a payload that cannot be read.
+    Api = 3,
+    /// The API could not be reached.
+    Network = 4,
+}
+
+impl From<&ApiError> for Exit {
+    fn from(err: &ApiError) -> Self {
+        match err {
+            ApiError::Transport(_) => Exit::Network,
+            ApiError::UnknownCurrency { .. } | ApiError::InvalidArguments { .. } => Exit::Usage,
+            _ => Exit::Api,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+/// The function `run` executes `command` against CurrencyBeacon. Unlike the menu it does not fall
+/// back to the snapshot or ECB rates, so the exit code tells what went wrong with the API. The
+/// rates it fetches are still recorded in `snapshot` for when the API is down.
+///
+/// Returns:
+///
+/// `Exit::Success`, or the exit code matching the error, which has been shown on stderr.
+pub async fn run(command: ApiCommand, client: &CurrencyBeaconClient, snapshot: Arc<SnapshotProvider>, console: Console) -> Exit {
+    let provider: &dyn RateProvider = &RecordingProvider::new(client.clone(), snapshot);
+    let printer = console.printer();
+    let result: Result<Exit, ApiError> = match command {
+        ApiCommand::Convert { amount, from, to, date, rounding } => {
+            provider.convert(ExchangeCurrencyArguments {
+                from,
+                to,
+                amount,
+                date,
+                rounding: RoundingPolicy { mode: rounding, places: None },
+            }).await.map(|conversion| {
+                info!("{} converted to {} at the rate of {}", conversion.from, conversion.to, conversion.date);
+                show(console, &ConversionView::from(&conversion))
+            })
+        },
+        ApiCommand::Rates { base, symbols, date } => {
+            let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.trim().to_ascii_uppercase()).filter(|symbol| !symbol.is_empty()).collect();
+            let rates = match date {
+                Some(date) => provider.historical(HistoricalRatesArguments {
+                    date,
+                    base,
+                    symbols: if symbols.is_empty() { None } else { Some(symbols.clone()) },
+                }).await,
+                None => provider.latest(RatiosListArguments { base }).await,
+            };
+            rates.map(|rates| {
+                info!("Rates against {} effective on {}", rates.base, rates.date);
+                show(console, &RatesView::new(&rates, &symbols, printer.sort))
+            })
+        },
+        ApiCommand::Currencies => provider.currencies().await.map(|list| show(console, &CurrenciesView::new(&list, printer.sort))),
+        ApiCommand::Health => (Request { client }).call().await.map(|health| {
+            let shown = show(console, &HealthView::new(client.base_url(), &health));
+            if health.reachable {
+                shown
+            } else {
+                console.error(format!("API at {} cannot be reached", client.base_url()));
+                Exit::Network
+            }
+        }),
+    };
+    match result {
+        Ok(exit) => exit,
+        Err(err) => {
+            debug!("Command failed: {:?}", err);
+            console.error(&err);
+            Exit::from(&err)
+        },
+    }
+}
+
+/// The function `show` prints `view`, telling so on stderr when stdout cannot be written. A
+/// reader that stopped early, like `head`, is not an error.
+fn show(console: Console, view: &impl View) -> Exit {
+    match console.show(view) {
+        Ok(()) => Exit::Success,
+        Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Exit::Success,
+        Err(err) => {
+            console.error(format!("answer could not be printed: {}", err));
+            Exit::Failure
+        },
+    }
+}
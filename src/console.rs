@@ -0,0 +1,71 @@
+//! What the user of the `task` binary sees: messages and results go to stdout, errors to stderr,
+//! both without log prefixes. Diagnostics stay on the logger, whose verbosity is set
+//! by `-v` and `-q`.
+
+use std::fmt::Display;
+use std::io;
+
+use log::LevelFilter;
+
+use crate::output::{Printer, View};
+
+/// The function `log_level` turns the number of `-v` and `-q` flags into the level of the logger:
+/// warnings by default, then info, debug and trace with every `-v`, errors only or nothing with `-q`.
+pub fn log_level(verbose: u8, quiet: u8) -> LevelFilter {
+    const LEVELS: [LevelFilter; 6] = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+    let level = (2 + i16::from(verbose) - i16::from(quiet)).clamp(0, LEVELS.len() as i16 - 1);
+    LEVELS[level as usize]
+}
+
+/// The function `init_logger` sends the diagnostics of this crate to stderr at `level`, and
+/// those of its dependencies at `level` but never above warnings. `RUST_LOG` is applied on top,
+/// so it can still pick single modules.
+pub fn init_logger(level: LevelFilter) {
+    env_logger::Builder::new()
+        .filter_level(level.min(LevelFilter::Warn))
+        .filter_module(env!("CARGO_CRATE_NAME"), level)
+        .parse_env("RUST_LOG")
+        .init();
+}
+
+/// The presentation layer of the binary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Console {
+    printer: Printer,
+}
+
+impl Console {
+    pub fn new(printer: Printer) -> Self {
+        Console { printer }
+    }
+
+    pub fn printer(&self) -> Printer {
+        self.printer
+    }
+
+    /// The function `say` prints a line of text to stdout.
+    pub fn say(&self, message: impl Display) {
+        println!("{}", message);
+    }
+
+    /// The function `error` tells the user what went wrong, on stderr.
+    pub fn error(&self, message: impl Display) {
+        eprintln!("error: {}", message);
+    }
+
+    /// The function `show` prints a result in the chosen output format.
+    ///
+    /// Returns:
+    ///
+    /// The IO error raised while writing to stdout.
+    pub fn show(&self, view: &impl View) -> io::Result<()> {
+        self.printer.print(view)
+    }
+}
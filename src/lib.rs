@@ -1,230 +1,222 @@
-use serde_json;
 use crate::api::{
-    credentials::Credentials,
     arguments::{
-ExchangeCurrencyArguments,
+        ExchangeCurrencyArguments,
+        HistoricalRatesArguments,
         RatiosListArguments,
+        TimeSeriesArguments,
     }
 };
 
 pub mod api {
-    use log::{info, error};
-    use reqwest;
-    use crate::serde_json::Map;
-    use crate::Credentials;
     use crate::ExchangeCurrencyArguments;
+    use crate::HistoricalRatesArguments;
     use crate::RatiosListArguments;
-    use core::any::Any;
-    use std::collections::HashMap;
+    use crate::TimeSeriesArguments;
+    use models::{ConvertResponse, CurrencyList, Health, HistoricalResponse, LatestResponse, TimeSeriesResponse};
+    pub use client::{ClientConfig, CurrencyBeaconClient};
+    pub use error::ApiError;
+    pub use provider::RateProvider;
 
-    pub type Result<T> = std::result::Result<T, Box<(dyn std::error::Error + 'static)>>;
+    pub mod cache;
+    pub mod cassette;
+    pub mod client;
+    pub mod consensus;
+    pub mod ecb;
+    pub mod error;
+    pub mod failover;
+    pub mod models;
+    pub mod money;
+    pub mod provider;
+    pub mod rate_limit;
+    pub mod rate_table;
+    pub mod retry;
+    pub mod snapshot;
+    pub mod timeseries;
+    pub mod usage;
+
+    pub type Result<T> = std::result::Result<T, ApiError>;
 
     pub mod arguments {
+        use chrono::NaiveDate;
+        use rust_decimal::Decimal;
+        use super::money::RoundingPolicy;
+
+        #[derive(Debug, Clone)]
         pub struct RatiosListArguments{
             pub base: String,
         }
-        
-        pub struct ExchangeCurrencyArguments{
-            pub from: String,
-            pub to: String,
-            pub amount: f64,
+
+        #[derive(Debug, Clone)]
+        pub struct HistoricalRatesArguments{
+            pub date: NaiveDate,
+            pub base: String,
+            /// Limits the answer to these currencies, `None` returns all of them.
+            pub symbols: Option<Vec<String>>,
         }
-    }
-    pub mod credentials {
-        use std::env;
 
-        pub struct Credentials <'a> {
-            pub url: &'a str,
-            pub api_key: &'a str,
+        #[derive(Debug, Clone)]
+        pub struct TimeSeriesArguments{
+            pub start_date: NaiveDate,
+            /// Last day of the range, included.
+            pub end_date: NaiveDate,
+            pub base: String,
+            /// Limits the answer to these currencies, `None` returns all of them.
+            pub symbols: Option<Vec<String>>,
         }
-        
-        impl Credentials<'_> {
-            /// The `new` function in Rust initializes a new Credentials struct.
-            /// 
-            /// Returns:
-            /// 
-            /// An instance of the struct that the `new` function is defined in is being returned.
-            pub fn new<'a>() -> Self {
-                let url: String = match env::var("API_URL") {
-                    Ok(val) => val,
-                    Err(_) => panic!("API_URL is not defined in the environment"),
-                };
-                let api_key: String = match env::var("API_KEY") {
-                    Ok(val) => val,
-                    Err(_) => panic!("API_KEY is not defined in the environment"),
-                };
-        
-                Credentials { url: Box::leak(url.into_boxed_str()), api_key: Box::leak(api_key.into_boxed_str()) }     
-            }
+
+        #[derive(Debug, Clone)]
+        pub struct ExchangeCurrencyArguments{
+            pub from: String,
+            pub to: String,
+            pub amount: Decimal,
+            /// Converts at the rate of this day instead of the current one.
+            pub date: Option<NaiveDate>,
+            /// How the converted value is rounded, by default half up to the minor units of `to`.
+            pub rounding: RoundingPolicy,
         }
     }
+    pub mod credentials;
+    /// Every request knows the concrete type it produces, so callers get a typed value
+    /// back instead of having to guess what sits inside a `Box<dyn Any>`.
     pub trait Requests {
-        fn call(&mut self) -> impl std::future::Future<Output = Result<Box<dyn Any>>> + Send;
+        type Output;
+
+        fn call(&mut self) -> impl std::future::Future<Output = Result<Self::Output>> + Send;
     }
 
     pub struct Request<'a>{
-        pub credentials: &'a Credentials<'a>
+        pub client: &'a CurrencyBeaconClient
     }
-    
+
     pub struct CurrencyListRequest<'a>{
-        pub credentials: &'a Credentials<'a>
+        pub client: &'a CurrencyBeaconClient
     }
 
     pub struct RatiosListRequest<'a>{
         pub args: RatiosListArguments,
-        pub credentials: &'a Credentials<'a>
+        pub client: &'a CurrencyBeaconClient
     }
-    
+
+    pub struct HistoricalRatesRequest<'a>{
+        pub args: HistoricalRatesArguments,
+        pub client: &'a CurrencyBeaconClient
+    }
+
+    pub struct TimeSeriesRequest<'a>{
+        pub args: TimeSeriesArguments,
+        pub client: &'a CurrencyBeaconClient
+    }
+
     pub struct ExchangeCurrencyRequest<'a>{
         pub args: ExchangeCurrencyArguments,
-        pub credentials: &'a Credentials<'a>
+        pub client: &'a CurrencyBeaconClient
     }
 /// The `impl Requests for ExchangeCurrencyRequest<'_>` block in the Rust code is implementing the `Requests` trait for
 /// the `ExchangeCurrencyRequest` struct. This means that the `ExchangeCurrencyRequest` struct will now have the behavior defined in the
 /// `Requests` trait.
     impl Requests for ExchangeCurrencyRequest<'_>{
-        /// This async function sends a request to a specified URL with parameters, retrieves JSON data, and
-        /// prints converted value and its currency based on the provided params.
-        /// 
+        type Output = ConvertResponse;
+
+        /// This async function asks the client to convert the amount given in the arguments and
+        /// returns the converted value together with the currencies it was converted between.
+        ///
         /// Returns:
-        /// 
-        /// The `call` function returns a `Result` with the success type `Box<dyn Any>` and the error type
-        /// `Box<dyn std::error::Error>`.
-        async fn call(&mut self) -> Result<Box<dyn Any>> {
-            let url: &str = &(self.credentials.url.to_string() + "/v1/convert");
-            let request = reqwest::Url::parse_with_params(url, &[
-                (
-                    "api_key",  self.credentials.api_key
-                ),
-                (
-                    "from", &self.args.from.to_ascii_uppercase()
-                ),
-                (
-                    "to", &self.args.to.to_ascii_uppercase()
-                ),
-                (
-                    "amount", &self.args.amount.to_string()
-                ),
-            ]
-            )?;
-            match reqwest::get(request).await {
-                Ok(resp) => {
-                    let json: serde_json::Value = resp.json().await?;
-                    let data: &Map<String, serde_json::Value> = &json.as_object().unwrap();
-                    if data["response"]["value"].to_string() != "[]" {
-                        let f: f32 = data["response"]["value"].to_string().parse().unwrap();
-                        return Ok(Box::new(f));
-                    }else{
-                        return Ok(Box::new(0));
-                    }
-                }
-                Err(_) => {
-                    return Err("Request got bad".into())
-                }
-            }
+        ///
+        /// The `call` function returns a `Result` with the success type `ConvertResponse` and the error type
+        /// `ApiError`. An unknown currency is reported as `ApiError::UnknownCurrency`.
+        async fn call(&mut self) -> Result<ConvertResponse> {
+            self.client.convert(&self.args).await
         }
     }
 /// The `impl Requests for RatiosListRequest<'_>` block in the Rust code is implementing the `Requests` trait for
 /// the `RatiosListRequest` struct. This means that the `RatiosListRequest` struct will now have the behavior defined in the
 /// `Requests` trait.
     impl Requests for RatiosListRequest<'_>{
-        /// This async function sends a request to a specified URL with parameters, retrieves JSON data, and
-        /// prints exchange rates based on the provided base currency.
-        /// 
+        type Output = LatestResponse;
+
+        /// This async function asks the client for exchange rates based on the provided base currency.
+        ///
+        /// Returns:
+        ///
+        /// The `call` function returns a `Result` with the success type `LatestResponse` and the error type
+        /// `ApiError`. A base currency that does not exist is reported as `ApiError::UnknownCurrency`.
+        async fn call(&mut self) -> Result<LatestResponse> {
+            self.client.latest(&self.args).await
+        }
+    }
+
+/// The `impl Requests for HistoricalRatesRequest<'_>` block in the Rust code is implementing the `Requests` trait for
+/// the `HistoricalRatesRequest` struct. This means that the `HistoricalRatesRequest` struct will now have the behavior defined in the
+/// `Requests` trait.
+    impl Requests for HistoricalRatesRequest<'_>{
+        type Output = HistoricalResponse;
+
+        /// This async function asks the client for the exchange rates of the base currency as they
+        /// were on the day given in the arguments.
+        ///
         /// Returns:
-        /// 
-        /// The `call` function returns a `Result` with the success type Box of dyn Any and the error type
-        /// `Box<dyn std::error::Error>`.
-        async fn call(&mut self) -> Result<Box<dyn Any>> {
-            let url: &str = &(self.credentials.url.to_string() + "/v1/latest");
-            let request = reqwest::Url::parse_with_params(url, &[
-                (
-                    "api_key",  self.credentials.api_key
-                ),
-                (
-                    "base", &self.args.base.to_ascii_uppercase()
-                )
-            ])?;
-
-            match reqwest::get(request).await {
-                Ok(resp) => {
-                    let json: serde_json::Value = resp.json().await?;
-                    let data = json.as_object().unwrap().iter();
-                    let mut ratios: String = "[]".to_string();
-                    for (_,value) in data {
-                        if value["rates"].to_string() != "null" && value["rates"].to_string() != "[]" {
-                            ratios = value["rates".to_string()].to_string();
-                            break;
-                        }
-                    }
-                    return Ok(Box::new(ratios));
-                }
-                Err(_err) => {
-                    return Err("Request went bad".into());
-                }
-            }
+        ///
+        /// The `call` function returns a `Result` with the success type `HistoricalResponse`, which carries the
+        /// date the rates are effective for, and the error type `ApiError`.
+        async fn call(&mut self) -> Result<HistoricalResponse> {
+            self.client.historical(&self.args).await
         }
     }
-    
+
+/// The `impl Requests for TimeSeriesRequest<'_>` block in the Rust code is implementing the `Requests` trait for
+/// the `TimeSeriesRequest` struct. This means that the `TimeSeriesRequest` struct will now have the behavior defined in the
+/// `Requests` trait.
+    impl Requests for TimeSeriesRequest<'_>{
+        type Output = TimeSeriesResponse;
+
+        /// This async function asks the client for the daily exchange rates of the base currency over
+        /// the range given in the arguments. Long ranges are fetched in several calls and merged.
+        ///
+        /// Returns:
+        ///
+        /// The `call` function returns a `Result` with the success type `TimeSeriesResponse`, ordered by date,
+        /// and the error type `ApiError`.
+        async fn call(&mut self) -> Result<TimeSeriesResponse> {
+            self.client.timeseries(&self.args).await
+        }
+    }
+
 /// The `impl Requests for CurrencyListRequest<'_>` block in the Rust code is implementing the `Requests` trait for
 /// the `CurrencyListRequest` struct. This means that the `CurrencyListRequest` struct will now have the behavior defined in the
 /// `Requests` trait.
     impl Requests for CurrencyListRequest<'_>{
-        /// The function `call` sends a request to a specified URL, retrieves JSON data, and prints out the
-        /// short code and name of each currency in the response.
+        type Output = CurrencyList;
+
+        /// The function `call` asks the client for the short code and name of each currency the API knows.
         // Add API_KEY and API_URL=https://api.currencybeacon.com values in ~/.bashrc
         // You can generate API KEY on https://api.currencybeacon.com
-        /// 
+        ///
         /// Returns:
-        /// 
-        /// The `call` function is returning a `Result` enum with the success variant containing `Box<dyn Any>' 
-        ///  and the error variant containing a boxed trait object that implements the `std::error::Error`
-        /// trait.
-        async fn call(&mut self) -> Result<Box<dyn Any>> {
-            let url: &str = &(self.credentials.url.to_string() + "/v1/currencies?api_key=" + self.credentials.api_key);
-            match reqwest::get(url).await{
-                Ok(resp) => {
-                    let json: serde_json::Value = resp.json().await?;
-                    let data: Map<String, serde_json::Value> = json.as_object().unwrap().clone();
-                    let mut result: HashMap<std::string::String, std::string::String> = HashMap::new();
-                    for (_, value) in data.into_iter() {
-                        match value["short_code"].to_string().as_ref(){
-                            "null" => {},
-                            _ => {
-                                result.insert(value["short_code"].to_string(), value["name"].to_string());
-                            }
-                        }
-                    }
-                    return Ok(Box::new(result));
-                }
-                Err(_) => {
-                    return Err("Request went bad".into());
-                }
-            };    
+        ///
+        /// The `call` function is returning a `Result` enum with the success variant containing `CurrencyList`
+        ///  and the error variant containing an `ApiError`.
+        async fn call(&mut self) -> Result<CurrencyList> {
+            self.client.currencies().await
         }
     }
-    
+
 /// The `impl Requests for Request<'_>` block in the Rust code is implementing the `Requests` trait for
 /// the `Request` struct. This means that the `Request` struct will now have the behavior defined in the
 /// `Requests` trait.
     impl Requests for Request<'_>{
+        type Output = Health;
+
         /// The function `call` sends a request to an external API to check out aliveness of the service
-        /// 
+        ///
         /// Returns:
-        /// 
-        /// The `call` function returns a `Result` enum with the success variant containing Box of dyn Any
-        ///  and the error variant containing a boxed `dyn std::error::Error` trait object.
-        async fn call(&mut self) -> Result<Box<dyn Any>> {
-            let url: &str = &(self.credentials.url.to_string() + "?api_key=" + self.credentials.api_key);
-            match reqwest::get(url).await {
-                Ok(_) => {
-                    info!("Request was sent to {}", self.credentials.url)
-                }
-                Err(err) => {
-                    error!("Request got an error: {}", err)
-                }
-            }
-            Ok(Box::new(()))
+        ///
+        /// The `call` function returns a `Result` enum with the success variant containing `Health`
+        ///  and the error variant containing an `ApiError`. An unreachable API is reported through `Health`.
+        async fn call(&mut self) -> Result<Health> {
+            self.client.health().await
         }
     }
 }
+
+#[cfg(feature = "mock")]
+pub mod mock;
@@ -1,148 +1,437 @@
 
-use crate::input::read_input;
-use std::collections::HashMap;
+use crate::cli::{Cli, Command, Exit};
+use crate::console::Console;
+use crate::output::{ConversionView, CurrenciesView, RatesView};
+use crate::repl::Repl;
 
 use task::api::{
-    ExchangeCurrencyRequest,
+    ApiError,
+    ClientConfig,
+    CurrencyBeaconClient,
+    RateProvider,
     Requests,
-    RatiosListRequest,
-    CurrencyListRequest,
     Request,
+    cache::{
+        CacheConfig,
+        CacheInfo,
+    },
     credentials::{
         Credentials,
     },
+    money::{
+        Money,
+        RoundingMode,
+        RoundingPolicy,
+    },
+    ecb::EcbProvider,
+    failover::FailoverProvider,
+    snapshot::{RecordingProvider, SnapshotProvider},
+    rate_table::RateTable,
+    usage::UsageConfig,
+    cassette::CassetteConfig,
     arguments::{
 ExchangeCurrencyArguments,
+        HistoricalRatesArguments,
         RatiosListArguments,
     }
 };
-use log::{LevelFilter, log_enabled, Level, info, error};
+use chrono::NaiveDate;
+use clap::Parser;
+use rust_decimal::Decimal;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
 
-mod input {
-    use std::io;
-    use crate::info;
-    pub fn read_input(prompt: &str) -> String{
-        info!("{}",prompt);
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_goes_into_input_above) => {},
-            Err(_no_updates_is_fine) => {},
-        }
-        input.trim().to_string()
+/// Conversions without a date are computed locally from one `latest` snapshot of this base,
+/// which is fetched again once it is older than `RATE_TABLE_MAX_AGE`.
+const RATE_TABLE_BASE: &str = "USD";
+const RATE_TABLE_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Points at an ECB `eurofxref` XML file or directory, the last resort when neither the API nor the snapshot can answer.
+const ECB_RATES_VAR: &str = "ECB_RATES";
+
+/// Overrides the monthly request allowance of the plan used for usage warnings.
+const MONTHLY_ALLOWANCE_VAR: &str = "API_MONTHLY_ALLOWANCE";
+
+/// Record every API exchange into, or replay it from, the cassette file they point at.
+const RECORD_CASSETTE_VAR: &str = "API_RECORD_CASSETTE";
+const REPLAY_CASSETTE_VAR: &str = "API_REPLAY_CASSETTE";
+
+mod cli;
+mod console;
+mod output;
+mod repl;
+mod tui;
+
+/// The function `log_origin` tells whether the answer to `operation` was fetched from `provider` or served from the cache.
+fn log_origin(provider: &str, operation: &str, cache: CacheInfo) {
+    if cache.from_cache {
+        info!("Answer to {} from {} was served from cache, {}s old", operation, provider, cache.age.as_secs());
+    } else {
+        info!("Request for {} was sent to {}", operation, provider);
     }
 }
 
-/// The above Rust code defines a main function that interacts with different API requests based on user
-/// input in a loop until the user chooses to exit the program.
+/// The function `load_currencies` hands the currency codes `provider` knows to `repl`, unless it
+/// has them already, so they are completed and answers are checked before any request is sent.
+async fn load_currencies(repl: &mut Repl, provider: &dyn RateProvider) {
+    if repl.knows_currencies() {
+        return;
+    }
+    match provider.currencies().await {
+        Ok(list) => {
+            log_origin(&list.provider, "currencies", list.cache);
+            repl.set_currencies(list.currencies.into_iter().map(|currency| currency.code));
+        },
+        Err(err) => warn!("Currency codes could not be fetched, answers are not checked: {}", err),
+    }
+}
+
+/// The above Rust code defines a main function that runs the subcommand given on the command line,
+/// shows the terminal UI, or interacts with different API requests based on user input in a loop until the user chooses
+/// to exit the program.
 /// 
 /// Returns:
 /// 
-/// The `main` function is returning a `Result<(), Box<dyn std::error::Error>>`. This means it will
-/// return `Ok(())` if the program runs successfully, and it will return an error if any errors occur
-/// during execution.
+/// The `main` function is returning a `Result<ExitCode, Box<dyn std::error::Error>>`. A subcommand
+/// exits with the code of `cli::Exit` matching its outcome, the menu with 0 once stdin is closed
+/// or 'exit' is typed, the terminal UI with 0 once it is closed; errors while setting up exit with 1.
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    env_logger::init();
-    log::set_max_level(LevelFilter::Debug);
-    if log_enabled!(Level::Debug) {
-        println!("LOG LEVEL DEBUG");
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let console = Console::new(cli.printer());
+    let sort = console.printer().sort;
+    crate::console::init_logger(cli.log_level());
+    debug!("Logging at {}", cli.log_level());
+    let credentials: Credentials = match Credentials::load() {
+        Ok(credentials) => credentials,
+        Err(err) => {
+            console.error(&err);
+            return Ok(Exit::Failure.into());
+        }
+    };
+    let mut usage = UsageConfig::default();
+    if let Ok(allowance) = std::env::var(MONTHLY_ALLOWANCE_VAR) {
+        match allowance.parse::<u64>() {
+            Ok(allowance) => usage.monthly_allowance = Some(allowance),
+            Err(_) => warn!("{} must be a whole number, using {:?}", MONTHLY_ALLOWANCE_VAR, usage.monthly_allowance),
+        }
+    }
+    let warn_threshold = usage.warn_threshold;
+    let cassette = match (std::env::var_os(RECORD_CASSETTE_VAR), std::env::var_os(REPLAY_CASSETTE_VAR)) {
+        (Some(_), Some(_)) => {
+            console.error(format!("set only one of {} and {}", RECORD_CASSETTE_VAR, REPLAY_CASSETTE_VAR));
+            return Ok(Exit::Failure.into());
+        },
+        (Some(path), None) => Some(CassetteConfig::record(path)),
+        (None, Some(path)) => Some(CassetteConfig::replay(path)),
+        (None, None) => None,
+    };
+    if let Some(cassette) = &cassette {
+        info!("API cassette {}: {:?}", cassette.path.display(), cassette.mode);
+    }
+    let client: &CurrencyBeaconClient = &match CurrencyBeaconClient::with_config(&credentials, ClientConfig{
+        cache: Some(CacheConfig::default()),
+        usage: Some(usage),
+        cassette,
+        ..ClientConfig::default()
+    }) {
+        Ok(client) => client,
+        Err(err) => {
+            console.error(&err);
+            return Ok(Exit::Failure.into());
+        }
+    };
+    if let Some(report) = client.usage() {
+        if report.used_fraction().unwrap_or_default() >= warn_threshold {
+            warn!("{} API requests sent in {}, past {:.0}% of the allowance of {:?}", report.total, report.month, warn_threshold * 100.0, report.allowance);
+        }
+    }
+    let snapshot: Arc<SnapshotProvider> = Arc::new(match SnapshotProvider::default_state_file().map(SnapshotProvider::open) {
+        Some(Ok(snapshot)) => snapshot,
+        Some(Err(err)) => {
+            warn!("Rate snapshot could not be loaded, starting without one: {}", err);
+            SnapshotProvider::in_memory()
+        },
+        None => SnapshotProvider::in_memory(),
+    });
+    let tui = match cli.command {
+        Some(Command::Tui) => true,
+        Some(Command::Api(command)) => return Ok(cli::run(command, client, snapshot, console).await.into()),
+        None => false,
+    };
+    let ecb: Option<EcbProvider> = match std::env::var_os(ECB_RATES_VAR) {
+        Some(path) => match EcbProvider::open(&path) {
+            Ok(ecb) => {
+                let (first, last) = ecb.range();
+                info!("ECB files in {} cover {} to {}", path.to_string_lossy(), first, last);
+                Some(ecb)
+            },
+            Err(err) => {
+                console.error(&err);
+                return Ok(Exit::Failure.into());
+            }
+        },
+        None => None,
+    };
+    let mut providers: Vec<Box<dyn RateProvider>> = vec![
+        Box::new(RecordingProvider::new(client.clone(), snapshot.clone())),
+        Box::new(snapshot.clone()),
+    ];
+    if let Some(ecb) = ecb {
+        providers.push(Box::new(ecb));
     }
-    let credentials: &mut Credentials<'_> = &mut Credentials::new();
+    let provider: &dyn RateProvider = &FailoverProvider::new(providers);
+    if tui {
+        let source = tui::Source{
+            provider,
+            client,
+            base: RATE_TABLE_BASE,
+            max_age: RATE_TABLE_MAX_AGE,
+        };
+        return Ok(match tui::run(source, sort).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                console.error(&err);
+                Exit::Failure.into()
+            }
+        });
+    }
+    let mut rate_table: Option<(Instant, RateTable, String)> = None;
+    let mut repl = Repl::open(Repl::default_history_file());
     loop {
-        info!("Type '1' => to check if an API is alive");
-        info!("Type '2' => to print currency list");
-        info!("Type '3' => to print ratios");
-        info!("Type '4' => to exchange currencies");
-        info!("Type 'exit' to exit program\n");
-        let action: &str = &read_input("Choose action");
+        console.say("Type '1' => to check if an API is alive");
+        console.say("Type '2' => to print currency list");
+        console.say("Type '3' => to print ratios");
+        console.say("Type '4' => to exchange currencies");
+        console.say("Type '5' => to print ratios on a date");
+        console.say("Type 'usage' => to print API requests sent this month");
+        console.say("Type 'exit' to exit program\n");
+        let Some(action) = repl.read("Choose action") else { break };
+        let action: &str = &action;
         match action{ 
             "1" => {
                 match (Request{
-                    credentials: credentials,
+                    client,
                 }).call().await{
+                    Ok(health) if health.reachable => {
+                        console.say("Success!")
+                    },
                     Ok(_) => {
-                        info!("Success!")
+                        console.error("Failure!")
                     },
-                    Err(_err) => {
-                        error!("Failure!")
+                    Err(err) => {
+                        console.error(format!("Failure! {}", err))
                     }
 
                 };
             },
             "2" => {
-                match (CurrencyListRequest{
-                    credentials: credentials,
-                }).call().await{
-                    Ok(resp) => {
-                        info!("Request was sent to {}/v1/currencies", credentials.url);
-                        let data = resp.downcast::<HashMap<String, String>>().unwrap();
-
-                        for (key, value) in data.into_iter() {
-                            println!("{}:{}", key, value)
-                        }
-                        info!("Success!")
+                match provider.currencies().await{
+                    Ok(list) => {
+                        log_origin(&list.provider, "currencies", list.cache);
+                        console.show(&CurrenciesView::new(&list, sort))?;
+                        console.say("Success!")
                     },
-                    Err(_err) => {
-                        error!("Failure!")
+                    Err(err) => {
+                        console.error(format!("Failure! {}", err))
                     }
 
                 };
             },
             "3" => {
-                let base: String = read_input("Choose param 'base'");
+                load_currencies(&mut repl, provider).await;
+                let Some(base) = repl.read("Choose param 'base'") else { break };
+                if !repl.unknown([base.as_str()]).is_empty() {
+                    console.error("You picked up wrong base currency, such does not exist");
+                    continue;
+                }
 
-                let data = RatiosListRequest{
-                    credentials: credentials,
-                    args: RatiosListArguments{
-                        base: base,
+                match provider.latest(RatiosListArguments{
+                    base,
+                }).await{
+                    Ok(data) => {
+                        log_origin(&data.provider, "latest rates", data.cache);
+                        console.show(&RatesView::new(&data, &[], sort))?;
+                        console.say("Success!");
+                        let provider = data.provider.clone();
+                        rate_table = Some((Instant::now(), RateTable::from(data), provider));
+                    },
+                    Err(ApiError::UnknownCurrency { .. }) => {
+                        console.error("You picked up wrong base currency, such does not exist")
+                    },
+                    Err(err) => {
+                        console.error(format!("Request got an error: {}", err))
+                    },
+                };
+            },
+            "4" => {
+                let Some(amount) = repl.read("Choose param 'amount'") else { break };
+                let parsed_amount: Decimal = match amount.parse::<Decimal>() {
+                    Ok(parsed) => parsed,
+                    Err(_) => {
+                        console.error("Amount must be number");
+                        continue;
                     }
-                }.call().await?;
-                info!("Request was sent to {}/v1/latest", credentials.url);
-                let rates = &data.downcast::<String>().unwrap();
-                if rates.to_string() == "[]" {
-                    error!("You picked up wrong base currency, such does not exist")
+                };
+                load_currencies(&mut repl, provider).await;
+                let Some(from) = repl.read("Choose param 'from'") else { break };
+                let Some(to) = repl.read("Choose param 'to'") else { break };
+                let unknown = repl.unknown([from.as_str(), to.as_str()]);
+                if !unknown.is_empty() {
+                    console.error(ApiError::UnknownCurrency { codes: unknown });
+                    continue;
                 }
-                else{
-                    info!("{}", rates.to_string());
-                    info!("Success!");
+                let Some(date) = repl.read("Choose param 'date' (YYYY-MM-DD, leave empty for today)") else { break };
+                let parsed_date: Option<NaiveDate> = if date.is_empty() {
+                    None
+                } else {
+                    match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+                        Ok(parsed) => Some(parsed),
+                        Err(_) => {
+                            console.error("Date must be in YYYY-MM-DD format");
+                            continue;
+                        }
+                    }
+                };
+                let Some(rounding) = repl.read("Choose param 'rounding' (half-even, half-up, floor, ceil, truncate, leave empty for half-up)") else { break };
+                let parsed_rounding: RoundingMode = if rounding.is_empty() {
+                    RoundingMode::default()
+                } else {
+                    match rounding.parse::<RoundingMode>() {
+                        Ok(parsed) => parsed,
+                        Err(err) => {
+                            console.error(&err);
+                            continue;
+                        }
+                    }
+                };
+                let rounding = RoundingPolicy{
+                    mode: parsed_rounding,
+                    places: None,
+                };
+                if parsed_date.is_none() {
+                    let stale = match &rate_table {
+                        Some((fetched_at, _, _)) => fetched_at.elapsed() > RATE_TABLE_MAX_AGE,
+                        None => true,
+                    };
+                    if stale {
+                        match provider.latest(RatiosListArguments{
+                            base: RATE_TABLE_BASE.to_string(),
+                        }).await{
+                            Ok(data) => {
+                                log_origin(&data.provider, "latest rates", data.cache);
+                                let provider = data.provider.clone();
+                                rate_table = Some((Instant::now(), RateTable::from(data), provider));
+                            },
+                            Err(err) => {
+                                console.error(format!("Request got an error: {}", err));
+                                continue;
+                            }
+                        };
+                    }
+                    if let Some((_, table, provider)) = &rate_table {
+                        match table.convert(&Money::new(parsed_amount, &from), &to, rounding) {
+                            Ok(conversion) => {
+                                info!("{}'ve been converted to {}", conversion.from, conversion.to);
+                                info!("Rate {} via {} from the {} snapshot of {}", conversion.rate, conversion.path, conversion.snapshot.base, conversion.snapshot.date);
+                                console.show(&ConversionView::from_table(&conversion, provider))?;
+                                console.say("Success!")
+                            },
+                            Err(err) => {
+                                console.error(format!("Conversion got an error: {}", err))
+                            }
+                        };
+                    }
+                    continue;
                 }
+                match provider.convert(ExchangeCurrencyArguments{
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    amount: parsed_amount,
+                    date: parsed_date,
+                    rounding,
+                }).await{
+                    Ok(conversion) => {
+                        log_origin(&conversion.provider, "conversion", conversion.cache);
+                        info!("{}'ve been converted to {} at the rate of {}", conversion.from, conversion.to, conversion.date);
+                        console.show(&ConversionView::from(&conversion))?;
+                        console.say("Success!")
+                    },
+                    Err(err) => {
+                        console.error(format!("Request got an error: {}", err))
+                    }
+
+                };
             },
-            "4" => {
-                let amount: String = read_input("Choose param 'amount'");
-                if amount.parse::<f64>().is_ok() == false {
-                    error!("Amount must be number");
+            "5" => {
+                load_currencies(&mut repl, provider).await;
+                let Some(base) = repl.read("Choose param 'base'") else { break };
+                if !repl.unknown([base.as_str()]).is_empty() {
+                    console.error("You picked up wrong base currency, such does not exist");
                     continue;
                 }
-                let from: String = read_input("Choose param 'from'");
-                let to: String = read_input("Choose param 'to'");
-                let parsed_amount: f64 = amount.parse().unwrap();
-                match (ExchangeCurrencyRequest{
-                    credentials: credentials,
-                    args: ExchangeCurrencyArguments{
-                        from: from.to_string(),
-                        to: to.to_string(),
-                        amount: parsed_amount,
+                let Some(date) = repl.read("Choose param 'date' (YYYY-MM-DD)") else { break };
+                let parsed_date: NaiveDate = match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+                    Ok(parsed) => parsed,
+                    Err(_) => {
+                        console.error("Date must be in YYYY-MM-DD format");
+                        continue;
                     }
-                }).call().await{
-                    Ok(resp) => {
-                        let unboxed = resp.downcast::<f32>().unwrap();
-                        let value =(*unboxed * 100.00).round() / 100.00;
-                        info!("{}{}'ve been converted to {}{}", &amount, &from, value, to);
-                        println!("{:?}", value);
-                        info!("Success!")
+                };
+                let Some(symbols) = repl.read("Choose param 'symbols' (comma separated, leave empty for all)") else { break };
+                let parsed_symbols: Option<Vec<String>> = if symbols.is_empty() {
+                    None
+                } else {
+                    Some(symbols.split(',').map(|symbol| symbol.trim().to_string()).collect())
+                };
+                let unknown = parsed_symbols.as_ref().map(|symbols| repl.unknown(symbols.iter().map(String::as_str))).unwrap_or_default();
+                if !unknown.is_empty() {
+                    console.error(ApiError::UnknownCurrency { codes: unknown });
+                    continue;
+                }
+                match provider.historical(HistoricalRatesArguments{
+                    date: parsed_date,
+                    base,
+                    symbols: parsed_symbols,
+                }).await{
+                    Ok(data) => {
+                        log_origin(&data.provider, "historical rates", data.cache);
+                        info!("Rates effective on {}", data.date);
+                        console.show(&RatesView::new(&data, &[], sort))?;
+                        console.say("Success!");
                     },
-                    Err(_err) => {
-                        error!("Request got an error!")
+                    Err(ApiError::UnknownCurrency { .. }) => {
+                        console.error("You picked up wrong base currency, such does not exist")
+                    },
+                    Err(err) => {
+                        console.error(format!("Request got an error: {}", err))
+                    }
+                };
+            },
+            "usage" => {
+                match client.usage() {
+                    Some(report) => {
+                        for (endpoint, calls) in report.calls.iter() {
+                            console.say(format!("{}:{}", endpoint, calls))
+                        }
+                        match (report.allowance, report.used_fraction()) {
+                            (Some(allowance), Some(used)) => console.say(format!("{} of {} requests used in {} ({:.1}%)", report.total, allowance, report.month, used * 100.0)),
+                            _ => console.say(format!("{} requests sent in {}", report.total, report.month)),
+                        }
+                    },
+                    None => {
+                        console.error("API usage is not tracked")
                     }
-
                 };
             },
             "exit"=> {
                 break
             }, 
-            _=>info!("If you wished to exit, type 'exit'"),
+            _=>console.say("If you wished to exit, type 'exit'"),
         };
-    println!("") 
+    console.say("")
     }
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,543 @@
+//! A CurrencyBeacon-compatible HTTP server that answers from fixture data on localhost, so the
+//! real clients can be exercised end to end without network access or an API key. It backs the
+//! `mock_server` binary and the integration tests.
+//!
+//! A `FaultProfile` makes it misbehave on purpose: slow answers, 429s, 500s, truncated JSON,
+//! wrong content types and connection resets, scripted by request number or at random.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use log::{debug, warn};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::api::credentials::Credentials;
+use crate::api::models::Currency;
+
+/// Requests larger than this are cut off; every CurrencyBeacon request is a short GET.
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
+
+/// The API key the mock accepts unless told otherwise.
+pub const MOCK_API_KEY: &str = "mock-key";
+
+/// The data the mock answers with. Every rate is quoted against `base`; other bases are
+/// crossed through it, like the real API does.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixtures {
+    pub base: String,
+    pub latest: LatestFixture,
+    /// Rates per day, served by `/v1/historical` and `/v1/timeseries`.
+    pub historical: BTreeMap<NaiveDate, BTreeMap<String, Decimal>>,
+    pub currencies: Vec<Currency>,
+}
+
+/// The answer of `/v1/latest` against the fixture base.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LatestFixture {
+    pub date: String,
+    pub rates: BTreeMap<String, Decimal>,
+}
+
+impl Default for Fixtures {
+    /// The fixtures bundled with the crate, see `fixtures/currencybeacon.json`.
+    fn default() -> Self {
+        Fixtures::from_json(include_str!("../fixtures/currencybeacon.json")).expect("bundled fixtures are valid")
+    }
+}
+
+impl Fixtures {
+    /// The function `from_json` reads fixtures in the format of `fixtures/currencybeacon.json`.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// The function `from_file` reads fixtures from `path`.
+    ///
+    /// Returns:
+    ///
+    /// The fixtures, or the IO error raised while reading or decoding the file.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Fixtures::from_json(&json).map_err(io::Error::from)
+    }
+
+    /// The function `rebase` turns `rates`, quoted against the fixture base, into rates
+    /// against `base`. `None` when `base` is not known.
+    fn rebase(&self, rates: &BTreeMap<String, Decimal>, base: &str) -> Option<BTreeMap<String, Decimal>> {
+        let per_base = |code: &str| if code == self.base { Some(Decimal::ONE) } else { rates.get(code).copied() };
+        let base_rate = per_base(base).filter(|rate| !rate.is_zero())?;
+        Some(rates.keys().map(String::as_str)
+            .chain(std::iter::once(self.base.as_str()))
+            .filter(|code| *code != base)
+            .filter_map(|code| per_base(code).map(|rate| (code.to_string(), rate / base_rate)))
+            .collect())
+    }
+
+    /// The rates of the last fixture day on or before `date`.
+    fn day(&self, date: NaiveDate) -> Option<(NaiveDate, &BTreeMap<String, Decimal>)> {
+        self.historical.range(..=date).next_back().map(|(day, rates)| (*day, rates))
+    }
+}
+
+/// A way the mock can misbehave instead of answering normally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Waits this long, then answers normally.
+    Latency(Duration),
+    /// Answers 429, with a `Retry-After` header in whole seconds when set.
+    RateLimited { retry_after: Option<Duration> },
+    /// Answers with this status and a CurrencyBeacon error payload, e.g. 500 or 503.
+    Status(u16),
+    /// Answers 200 with only the first half of the normal JSON body.
+    TruncatedJson,
+    /// Answers 200 with an HTML page instead of JSON.
+    WrongContentType,
+    /// Reads the request and resets the connection without answering.
+    Reset,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::Latency(delay) => write!(f, "latency:{}", delay.as_millis()),
+            Fault::RateLimited { retry_after: Some(retry_after) } => write!(f, "429:{}", retry_after.as_secs()),
+            Fault::RateLimited { retry_after: None } => write!(f, "429"),
+            Fault::Status(status) => write!(f, "{}", status),
+            Fault::TruncatedJson => write!(f, "truncated"),
+            Fault::WrongContentType => write!(f, "html"),
+            Fault::Reset => write!(f, "reset"),
+        }
+    }
+}
+
+impl FromStr for Fault {
+    type Err = String;
+
+    /// Accepts the names printed by `Display`: `latency:MILLIS`, `429`, `429:SECONDS`, any other
+    /// status code such as `500`, `truncated`, `html` and `reset`.
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        let (name, argument) = match value.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (value.as_str(), None),
+        };
+        let number = |argument: Option<&str>| -> std::result::Result<Option<u64>, String> {
+            argument.map(|argument| argument.parse::<u64>().map_err(|err| format!("fault '{}': {}", value, err))).transpose()
+        };
+        match name {
+            "latency" => match number(argument)? {
+                Some(millis) => Ok(Fault::Latency(Duration::from_millis(millis))),
+                None => Err(format!("fault '{}' needs a delay in milliseconds, e.g. latency:500", value)),
+            },
+            "429" => Ok(Fault::RateLimited { retry_after: number(argument)?.map(Duration::from_secs) }),
+            "truncated" => Ok(Fault::TruncatedJson),
+            "html" => Ok(Fault::WrongContentType),
+            "reset" => Ok(Fault::Reset),
+            status => match status.parse::<u16>() {
+                Ok(status) if (400..600).contains(&status) => Ok(Fault::Status(status)),
+                _ => Err(format!("unknown fault '{}', expected latency:MILLIS, 429[:SECONDS], a status code, truncated, html or reset", value)),
+            },
+        }
+    }
+}
+
+/// Which requests of a `MockServer` get a `Fault`. Requests are numbered from 1 in the order
+/// they arrive. A scripted fault for a request number wins; otherwise every random fault is
+/// rolled in turn. With a seed the random faults hit the same requests on every run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultProfile {
+    script: BTreeMap<usize, Fault>,
+    random: Vec<(f64, Fault)>,
+    seed: Option<u64>,
+}
+
+impl FaultProfile {
+    /// The function `none` creates a profile without faults, the default.
+    pub fn none() -> Self {
+        FaultProfile::default()
+    }
+
+    /// The function `on_request` makes request number `request` fail with `fault`.
+    pub fn on_request(mut self, request: usize, fault: Fault) -> Self {
+        self.script.insert(request, fault);
+        self
+    }
+
+    /// The function `first` makes the first `requests` requests fail with `fault`.
+    pub fn first(mut self, requests: usize, fault: Fault) -> Self {
+        for request in 1..=requests {
+            self.script.insert(request, fault.clone());
+        }
+        self
+    }
+
+    /// The function `with_probability` makes every request fail with `fault` at the given
+    /// probability, between 0 and 1.
+    pub fn with_probability(mut self, probability: f64, fault: Fault) -> Self {
+        self.random.push((probability.clamp(0.0, 1.0), fault));
+        self
+    }
+
+    /// The function `seeded` fixes the seed random faults are rolled with.
+    pub fn seeded(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Whether the profile has no faults at all.
+    pub fn is_empty(&self) -> bool {
+        self.script.is_empty() && self.random.is_empty()
+    }
+}
+
+/// Settings of a `MockServer`.
+#[derive(Debug, Clone)]
+pub struct MockConfig {
+    pub fixtures: Fixtures,
+    /// The only `api_key` that is accepted, anything else is answered with 401.
+    pub api_key: String,
+    /// Where to listen. Port 0 picks a free one, see `MockServer::url`.
+    pub addr: SocketAddr,
+    /// How the server misbehaves, by default not at all.
+    pub faults: FaultProfile,
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        MockConfig {
+            fixtures: Fixtures::default(),
+            api_key: MOCK_API_KEY.to_string(),
+            addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            faults: FaultProfile::none(),
+        }
+    }
+}
+
+/// A request the mock received. The `api_key` is left out of `params`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    pub path: String,
+    pub params: BTreeMap<String, String>,
+}
+
+/// A running mock. It stops when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    api_key: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// The function `start` binds `config.addr` and serves requests in the background.
+    ///
+    /// Returns:
+    ///
+    /// The running server, or the IO error raised while binding the address.
+    pub async fn start(config: MockConfig) -> io::Result<MockServer> {
+        let listener = TcpListener::bind(config.addr).await?;
+        let addr = listener.local_addr()?;
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let api_key = config.api_key.clone();
+        let rng = match config.faults.seed {
+            Some(seed) => fastrand::Rng::with_seed(seed),
+            None => fastrand::Rng::new(),
+        };
+        let state = Arc::new(State { config, requests: requests.clone(), rng: Mutex::new(rng) });
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let state = state.clone();
+                        tokio::spawn(async move {
+                            if let Err(err) = state.serve(stream).await {
+                                debug!("Mock connection ended with an error: {}", err);
+                            }
+                        });
+                    }
+                    Err(err) => warn!("Mock could not accept a connection: {}", err),
+                }
+            }
+        });
+        Ok(MockServer { addr, api_key, requests, task })
+    }
+
+    /// The address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The url to use as `API_URL`, e.g. `http://127.0.0.1:38211`.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// The function `credentials` returns credentials the server accepts.
+    pub fn credentials(&self) -> Credentials {
+        Credentials { url: self.url(), api_key: self.api_key.clone() }
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        lock(&self.requests).clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// A response before it is written: the HTTP status, extra headers and the body.
+struct Reply {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Reply {
+    fn json(status: u16, body: Value) -> Self {
+        Reply { status, headers: Vec::new(), content_type: "application/json", body: body.to_string() }
+    }
+
+    fn ok(response: Value) -> Self {
+        Reply::json(200, json!({ "meta": { "code": 200, "disclaimer": "Mock data, see fixtures/currencybeacon.json" }, "response": response }))
+    }
+
+    fn error(status: u16, error_type: &str, error_detail: &str) -> Self {
+        Reply::json(status, json!({ "meta": { "code": status, "error_type": error_type, "error_detail": error_detail }, "response": [] }))
+    }
+}
+
+struct State {
+    config: MockConfig,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    rng: Mutex<fastrand::Rng>,
+}
+
+impl State {
+    async fn serve(&self, mut stream: TcpStream) -> io::Result<()> {
+        let head = read_head(&mut stream).await?;
+        let (reply, fault) = match parse_request_line(&head) {
+            Some((method, target)) if method == "GET" => self.route(&target),
+            Some(_) => (Reply::error(405, "method not allowed", "Only GET requests are supported."), None),
+            None => (Reply::error(400, "bad request", "The request line could not be read."), None),
+        };
+        let reply = match fault {
+            Some(fault) => {
+                debug!("Mock injects fault {}", fault);
+                match fault {
+                    Fault::Latency(delay) => {
+                        tokio::time::sleep(delay).await;
+                        reply
+                    }
+                    Fault::RateLimited { retry_after } => {
+                        let mut limited = Reply::error(429, "rate limit exceeded", "You have exceeded the allowed number of requests.");
+                        if let Some(retry_after) = retry_after {
+                            limited.headers.push(("Retry-After", retry_after.as_secs().to_string()));
+                        }
+                        limited
+                    }
+                    Fault::Status(status) => Reply::error(status, "server error", "The mock was told to fail this request."),
+                    Fault::TruncatedJson => {
+                        let cut = reply.body.char_indices().map(|(index, _)| index).nth(reply.body.chars().count() / 2).unwrap_or_default();
+                        Reply { body: reply.body[..cut].to_string(), ..reply }
+                    }
+                    Fault::WrongContentType => Reply {
+                        status: 200,
+                        headers: Vec::new(),
+                        content_type: "text/html; charset=utf-8",
+                        body: "<!DOCTYPE html><html><body><h1>Service Temporarily Unavailable</h1></body></html>".to_string(),
+                    },
+                    Fault::Reset => {
+                        // A zero linger makes dropping the socket send RST instead of FIN.
+                        #[allow(deprecated)]
+                        stream.set_linger(Some(Duration::ZERO))?;
+                        return Ok(());
+                    }
+                }
+            }
+            None => reply,
+        };
+        write_reply(&mut stream, reply).await
+    }
+
+    /// The function `fault_for` picks the fault of request number `request`, if it gets one.
+    fn fault_for(&self, request: usize) -> Option<Fault> {
+        let faults = &self.config.faults;
+        if let Some(fault) = faults.script.get(&request) {
+            return Some(fault.clone());
+        }
+        let mut rng = lock(&self.rng);
+        faults.random.iter().find(|(probability, _)| rng.f64() < *probability).map(|(_, fault)| fault.clone())
+    }
+
+    /// The function `route` records the request and answers it from the fixtures.
+    ///
+    /// Returns:
+    ///
+    /// The normal reply, and the fault the profile picked for this request.
+    fn route(&self, target: &str) -> (Reply, Option<Fault>) {
+        let Ok(url) = reqwest::Url::parse(&format!("http://mock{}", target)) else {
+            return (Reply::error(400, "bad request", "The request target is not a valid url."), None);
+        };
+        let mut params: BTreeMap<String, String> = url.query_pairs().map(|(key, value)| (key.into_owned(), value.into_owned())).collect();
+        let api_key = params.remove("api_key");
+        let path = url.path().trim_end_matches('/').to_string();
+        debug!("Mock GET {} {:?}", path, params);
+        let request = {
+            let mut requests = lock(&self.requests);
+            requests.push(MockRequest { path: path.clone(), params: params.clone() });
+            requests.len()
+        };
+        (self.answer(&path, &params, api_key.as_deref()), self.fault_for(request))
+    }
+
+    fn answer(&self, path: &str, params: &BTreeMap<String, String>, api_key: Option<&str>) -> Reply {
+        if api_key != Some(self.config.api_key.as_str()) {
+            return Reply::error(401, "auth failed", "You need to provide a valid API key.");
+        }
+        let fixtures = &self.config.fixtures;
+        let base = || params.get("base").map(|base| base.trim().to_ascii_uppercase()).unwrap_or_else(|| fixtures.base.clone());
+        let symbols = || params.get("symbols").map(|symbols| symbols.split(',').map(|symbol| symbol.trim().to_ascii_uppercase()).collect::<Vec<String>>());
+        match path {
+            "" => Reply::ok(json!({ "status": "ok" })),
+            "/v1/latest" => {
+                let base = base();
+                let rates = fixtures.rebase(&fixtures.latest.rates, &base).map(|rates| only(rates, symbols()));
+                Reply::ok(json!({ "date": fixtures.latest.date, "base": base, "rates": rates_json(rates) }))
+            }
+            "/v1/historical" => {
+                let Some(date) = date_param(params, "date") else {
+                    return Reply::error(422, "validation error", "The date must be a date in the YYYY-MM-DD format.");
+                };
+                let base = base();
+                let found = fixtures.day(date).and_then(|(day, rates)| Some((day, only(fixtures.rebase(rates, &base)?, symbols()))));
+                let day = found.as_ref().map_or(date, |(day, _)| *day);
+                Reply::ok(json!({ "date": day.to_string(), "base": base, "rates": rates_json(found.map(|(_, rates)| rates)) }))
+            }
+            "/v1/timeseries" => {
+                let (Some(start), Some(end)) = (date_param(params, "start_date"), date_param(params, "end_date")) else {
+                    return Reply::error(422, "validation error", "The start_date and end_date must be dates in the YYYY-MM-DD format.");
+                };
+                let base = base();
+                let days: serde_json::Map<String, Value> = fixtures.historical.range(start..=end)
+                    .filter_map(|(day, rates)| Some((day.to_string(), rates_json(Some(only(fixtures.rebase(rates, &base)?, symbols()))))))
+                    .collect();
+                if days.is_empty() { Reply::ok(json!([])) } else { Reply::ok(Value::Object(days)) }
+            }
+            "/v1/convert" => {
+                let (Some(from), Some(to), Some(amount)) = (params.get("from"), params.get("to"), params.get("amount")) else {
+                    return Reply::error(422, "validation error", "The from, to and amount parameters are required.");
+                };
+                let Ok(amount) = Decimal::from_str(amount.trim()) else {
+                    return Reply::error(422, "validation error", "The amount must be a number.");
+                };
+                let (from, to) = (from.trim().to_ascii_uppercase(), to.trim().to_ascii_uppercase());
+                let rate = if from == to {
+                    fixtures.rebase(&fixtures.latest.rates, &from).map(|_| Decimal::ONE)
+                } else {
+                    fixtures.rebase(&fixtures.latest.rates, &from).and_then(|rates| rates.get(&to).copied())
+                };
+                let value = match rate.map(|rate| amount.checked_mul(rate)) {
+                    Some(Some(value)) => number(value),
+                    Some(None) => return Reply::error(422, "validation error", "The amount is too large."),
+                    None => json!([]),
+                };
+                Reply::ok(json!({
+                    "timestamp": chrono::DateTime::parse_from_rfc3339(&fixtures.latest.date).map(|date| date.timestamp()).unwrap_or_default(),
+                    "date": fixtures.latest.date.get(..10).unwrap_or_default(),
+                    "from": from,
+                    "to": to,
+                    "amount": number(amount),
+                    "value": value,
+                }))
+            }
+            "/v1/currencies" => Reply::ok(json!(fixtures.currencies)),
+            _ => Reply::error(404, "not found", "The requested endpoint does not exist."),
+        }
+    }
+}
+
+/// The function `only` keeps the rates of `symbols`, or all of them without symbols.
+fn only(mut rates: BTreeMap<String, Decimal>, symbols: Option<Vec<String>>) -> BTreeMap<String, Decimal> {
+    if let Some(symbols) = symbols {
+        rates.retain(|code, _| symbols.contains(code));
+    }
+    rates
+}
+
+/// Rates as JSON numbers, or `[]` when there are none, the way CurrencyBeacon answers.
+fn rates_json(rates: Option<BTreeMap<String, Decimal>>) -> Value {
+    match rates {
+        Some(rates) if !rates.is_empty() => Value::Object(rates.into_iter().map(|(code, rate)| (code, number(rate))).collect()),
+        _ => json!([]),
+    }
+}
+
+/// CurrencyBeacon sends amounts and rates as JSON numbers, not strings. With serde_json's
+/// `arbitrary_precision` the number keeps every digit of the decimal.
+fn number(value: Decimal) -> Value {
+    Value::Number(value.to_string().parse().expect("a decimal is a valid JSON number"))
+}
+
+fn date_param(params: &BTreeMap<String, String>, name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(params.get(name)?.trim(), "%Y-%m-%d").ok()
+}
+
+/// The function `read_head` reads the request line and headers, up to the empty line.
+async fn read_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") && head.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+fn parse_request_line(head: &str) -> Option<(String, String)> {
+    let mut parts = head.lines().next()?.split_whitespace();
+    Some((parts.next()?.to_string(), parts.next()?.to_string()))
+}
+
+async fn write_reply(stream: &mut TcpStream, reply: Reply) -> io::Result<()> {
+    let reason = reqwest::StatusCode::from_u16(reply.status).ok().and_then(|status| status.canonical_reason()).unwrap_or("Unknown");
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status, reason, reply.content_type, reply.body.len(),
+    );
+    for (name, value) in reply.headers.iter() {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(reply.body.as_bytes()).await?;
+    stream.shutdown().await
+}
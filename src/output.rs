@@ -0,0 +1,353 @@
+//! How the `task` binary prints results: plain lines, JSON, CSV or an aligned table. Every
+//! result is first turned into a view with a fixed schema, so the formats only differ in layout.
+
+use std::cmp::Ordering;
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use task::api::models::Health;
+use task::api::provider::{Conversion, Currencies, Rates};
+use task::api::rate_table::TableConversion;
+
+/// The layout results are printed in, chosen with `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// The bare answer, e.g. `395.12` or one `CODE:RATE` per line.
+    #[default]
+    Plain,
+    /// One JSON object per result; amounts and rates are strings so no digit is lost.
+    Json,
+    /// A header line and one line per row, quoted as RFC 4180 asks.
+    Csv,
+    /// Aligned columns under a header, numbers right-aligned.
+    Table,
+}
+
+/// The column rows are sorted by, chosen with `--sort`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SortKey {
+    /// The currency code.
+    #[default]
+    Code,
+    /// The currency name; results without names are sorted by code.
+    Name,
+    /// The rate; results without rates are sorted by code.
+    Rate,
+}
+
+/// How the rows of a result are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sort {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl Sort {
+    /// The function `apply` orders `rows` by `compare`, reversed when descending.
+    pub fn apply<T>(&self, rows: &mut [T], compare: impl Fn(&T, &T) -> Ordering) {
+        rows.sort_by(|a, b| if self.descending { compare(b, a) } else { compare(a, b) });
+    }
+}
+
+/// How a column is aligned in table output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A column of CSV and table output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub align: Align,
+}
+
+const fn column(name: &'static str, align: Align) -> Column {
+    Column { name, align }
+}
+
+/// A result as every format sees it. The JSON form is the `Serialize` impl, CSV and table output
+/// share `columns` and `rows`.
+pub trait View: Serialize {
+    fn columns(&self) -> &'static [Column];
+    fn rows(&self) -> Vec<Vec<String>>;
+    /// The lines of plain output.
+    fn plain(&self) -> Vec<String>;
+}
+
+/// An amount converted into another currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConversionView {
+    pub from: String,
+    pub to: String,
+    pub amount: Decimal,
+    pub result: Decimal,
+    /// `null` when the source did not tell, e.g. for an amount of zero.
+    pub rate: Option<Decimal>,
+    pub date: String,
+    pub provider: String,
+}
+
+impl From<&Conversion> for ConversionView {
+    fn from(conversion: &Conversion) -> Self {
+        ConversionView {
+            from: conversion.from.currency.clone(),
+            to: conversion.to.currency.clone(),
+            amount: conversion.from.amount,
+            result: conversion.to.amount,
+            rate: conversion.rate,
+            date: conversion.date.to_string(),
+            provider: conversion.provider.clone(),
+        }
+    }
+}
+
+impl ConversionView {
+    /// The function `from_table` describes a conversion made locally from the rates `provider` answered with.
+    pub fn from_table(conversion: &TableConversion, provider: &str) -> Self {
+        ConversionView {
+            from: conversion.from.currency.clone(),
+            to: conversion.to.currency.clone(),
+            amount: conversion.from.amount,
+            result: conversion.to.amount,
+            rate: Some(conversion.rate),
+            date: conversion.snapshot.date.clone(),
+            provider: provider.to_string(),
+        }
+    }
+}
+
+impl View for ConversionView {
+    fn columns(&self) -> &'static [Column] {
+        const COLUMNS: &[Column] = &[
+            column("from", Align::Left),
+            column("to", Align::Left),
+            column("amount", Align::Right),
+            column("result", Align::Right),
+            column("rate", Align::Right),
+            column("date", Align::Left),
+            column("provider", Align::Left),
+        ];
+        COLUMNS
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.from.clone(),
+            self.to.clone(),
+            self.amount.to_string(),
+            self.result.to_string(),
+            self.rate.map(|rate| rate.to_string()).unwrap_or_default(),
+            self.date.clone(),
+            self.provider.clone(),
+        ]]
+    }
+
+    fn plain(&self) -> Vec<String> {
+        vec![self.result.to_string()]
+    }
+}
+
+/// The rate of one currency in a `RatesView`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RateRow {
+    pub code: String,
+    pub rate: Decimal,
+}
+
+/// Rates against a base currency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RatesView {
+    pub base: String,
+    pub date: String,
+    pub provider: String,
+    pub rates: Vec<RateRow>,
+}
+
+impl RatesView {
+    /// The function `new` keeps the rates of `symbols`, or all of them when there are none, ordered by `sort`.
+    pub fn new(rates: &Rates, symbols: &[String], sort: Sort) -> Self {
+        let mut rows: Vec<RateRow> = rates.rates.iter()
+            .filter(|(code, _)| symbols.is_empty() || symbols.iter().any(|symbol| symbol.eq_ignore_ascii_case(code)))
+            .map(|(code, rate)| RateRow { code: code.clone(), rate: *rate })
+            .collect();
+        sort.apply(&mut rows, |a, b| match sort.key {
+            SortKey::Rate => a.rate.cmp(&b.rate).then_with(|| a.code.cmp(&b.code)),
+            SortKey::Code | SortKey::Name => a.code.cmp(&b.code),
+        });
+        RatesView { base: rates.base.clone(), date: rates.date.to_string(), provider: rates.provider.clone(), rates: rows }
+    }
+}
+
+impl View for RatesView {
+    fn columns(&self) -> &'static [Column] {
+        const COLUMNS: &[Column] = &[
+            column("base", Align::Left),
+            column("date", Align::Left),
+            column("code", Align::Left),
+            column("rate", Align::Right),
+        ];
+        COLUMNS
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.rates.iter()
+            .map(|row| vec![self.base.clone(), self.date.clone(), row.code.clone(), row.rate.to_string()])
+            .collect()
+    }
+
+    fn plain(&self) -> Vec<String> {
+        self.rates.iter().map(|row| format!("{}:{}", row.code, row.rate)).collect()
+    }
+}
+
+/// A currency in a `CurrenciesView`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurrencyRow {
+    pub code: String,
+    /// `null` when the source has no name for it.
+    pub name: Option<String>,
+}
+
+/// Every currency a source knows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CurrenciesView {
+    pub provider: String,
+    pub currencies: Vec<CurrencyRow>,
+}
+
+impl CurrenciesView {
+    /// The function `new` lists the currencies ordered by `sort`.
+    pub fn new(currencies: &Currencies, sort: Sort) -> Self {
+        let mut rows: Vec<CurrencyRow> = currencies.currencies.iter()
+            .map(|currency| CurrencyRow { code: currency.code.clone(), name: currency.name.clone() })
+            .collect();
+        sort.apply(&mut rows, |a, b| match sort.key {
+            SortKey::Name => a.name.cmp(&b.name).then_with(|| a.code.cmp(&b.code)),
+            SortKey::Code | SortKey::Rate => a.code.cmp(&b.code),
+        });
+        CurrenciesView { provider: currencies.provider.clone(), currencies: rows }
+    }
+}
+
+impl View for CurrenciesView {
+    fn columns(&self) -> &'static [Column] {
+        const COLUMNS: &[Column] = &[column("code", Align::Left), column("name", Align::Left)];
+        COLUMNS
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.currencies.iter().map(|row| vec![row.code.clone(), row.name.clone().unwrap_or_default()]).collect()
+    }
+
+    fn plain(&self) -> Vec<String> {
+        self.currencies.iter().map(|row| format!("{}:{}", row.code, row.name.as_deref().unwrap_or_default())).collect()
+    }
+}
+
+/// Whether the API can be reached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthView {
+    pub url: String,
+    pub reachable: bool,
+    /// The HTTP status the API answered with, `null` when it was not reached.
+    pub status: Option<u16>,
+}
+
+impl HealthView {
+    pub fn new(url: &str, health: &Health) -> Self {
+        HealthView { url: url.to_string(), reachable: health.reachable, status: health.status }
+    }
+}
+
+impl View for HealthView {
+    fn columns(&self) -> &'static [Column] {
+        const COLUMNS: &[Column] = &[column("url", Align::Left), column("reachable", Align::Left), column("status", Align::Right)];
+        COLUMNS
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.url.clone(), self.reachable.to_string(), self.status.map(|status| status.to_string()).unwrap_or_default()]]
+    }
+
+    fn plain(&self) -> Vec<String> {
+        self.status.map(|status| status.to_string()).into_iter().collect()
+    }
+}
+
+/// Prints views to stdout in the chosen format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Printer {
+    pub format: OutputFormat,
+    pub sort: Sort,
+}
+
+impl Printer {
+    /// The function `print` writes `view` to stdout.
+    ///
+    /// Returns:
+    ///
+    /// The IO error raised while writing, e.g. when stdout was closed.
+    pub fn print(&self, view: &impl View) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        self.write(&mut out, view)?;
+        out.flush()
+    }
+
+    /// The function `write` writes `view` to `out`.
+    pub fn write(&self, out: &mut impl Write, view: &impl View) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Plain => {
+                for line in view.plain() {
+                    writeln!(out, "{}", line)?;
+                }
+            },
+            OutputFormat::Json => {
+                serde_json::to_writer(&mut *out, view)?;
+                writeln!(out)?;
+            },
+            OutputFormat::Csv => {
+                let header: Vec<String> = view.columns().iter().map(|column| column.name.to_string()).collect();
+                for row in std::iter::once(header).chain(view.rows()) {
+                    let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+                    writeln!(out, "{}", cells.join(","))?;
+                }
+            },
+            OutputFormat::Table => {
+                let columns = view.columns();
+                let header: Vec<String> = columns.iter().map(|column| column.name.to_ascii_uppercase()).collect();
+                let rows = view.rows();
+                let widths: Vec<usize> = (0..columns.len())
+                    .map(|index| std::iter::once(&header).chain(rows.iter())
+                        .map(|row| row.get(index).map_or(0, |cell| cell.chars().count()))
+                        .max()
+                        .unwrap_or_default())
+                    .collect();
+                let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+                for row in [header, separator].iter().chain(rows.iter()) {
+                    let cells: Vec<String> = columns.iter().zip(widths.iter()).zip(row.iter())
+                        .map(|((column, width), cell)| match column.align {
+                            Align::Left => format!("{:<width$}", cell, width = width),
+                            Align::Right => format!("{:>width$}", cell, width = width),
+                        })
+                        .collect();
+                    writeln!(out, "{}", cells.join("  ").trim_end())?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// The function `csv_cell` quotes `cell` when it holds a comma, a quote or a line break.
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
@@ -0,0 +1,217 @@
+//! How the menu of the `task` binary reads its answers. On a terminal lines are edited with
+//! rustyline: arrow keys recall earlier answers, kept in a history file between runs, and Tab
+//! completes currency codes. Piped input is read line by line, with the prompts still printed.
+
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use rustyline::completion::{Completer, Pair};
+use rustyline::config::{CompletionType, Config};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+
+/// How many answers the history file keeps.
+const HISTORY_SIZE: usize = 1000;
+
+/// Completes the word before the cursor with the currency codes that start with it.
+#[derive(Debug, Default)]
+struct CurrencyCompleter {
+    codes: BTreeSet<String>,
+}
+
+impl Completer for CurrencyCompleter {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let start = line[..pos].rfind(|c: char| c.is_whitespace() || c == ',').map_or(0, |index| index + 1);
+        let prefix = line[start..pos].to_ascii_uppercase();
+        let candidates = self.codes.iter()
+            .filter(|code| code.starts_with(&prefix))
+            .map(|code| Pair { display: code.clone(), replacement: code.clone() })
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for CurrencyCompleter {
+    type Hint = String;
+}
+
+impl Highlighter for CurrencyCompleter {}
+
+impl Validator for CurrencyCompleter {}
+
+impl Helper for CurrencyCompleter {}
+
+/// Reads the answers to the prompts of the menu.
+pub struct Repl {
+    /// `None` when stdin is not a terminal or the line editor could not be set up.
+    editor: Option<Editor<CurrencyCompleter, FileHistory>>,
+    history: Option<PathBuf>,
+    /// The codes answers are checked against, `None` until they are known.
+    currencies: Option<BTreeSet<String>>,
+}
+
+impl Repl {
+    /// The function `open` sets up line editing when stdin is a terminal and loads the history
+    /// from `history`. Without a terminal, or when rustyline cannot be set up, lines are read as they come.
+    pub fn open(history: Option<PathBuf>) -> Self {
+        let editor = if io::stdin().is_terminal() { Self::editor(history.as_ref()) } else { None };
+        Repl { editor, history, currencies: None }
+    }
+
+    fn editor(history: Option<&PathBuf>) -> Option<Editor<CurrencyCompleter, FileHistory>> {
+        let config = Config::builder()
+            .max_history_size(HISTORY_SIZE)
+            .and_then(|builder| builder.history_ignore_dups(true))
+            .map(|builder| builder.auto_add_history(true).completion_type(CompletionType::List).build());
+        let mut editor = match config.and_then(Editor::with_config) {
+            Ok(editor) => editor,
+            Err(err) => {
+                warn!("Line editing is not available: {}", err);
+                return None;
+            },
+        };
+        editor.set_helper(Some(CurrencyCompleter::default()));
+        if let Some(path) = history {
+            match editor.load_history(path) {
+                Ok(()) => debug!("History loaded from {}", path.display()),
+                Err(ReadlineError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {},
+                Err(err) => warn!("History {} could not be loaded: {}", path.display(), err),
+            }
+        }
+        Some(editor)
+    }
+
+    /// The history file inside the XDG data directory, usually `~/.local/share/task/history.txt`.
+    pub fn default_history_file() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("history.txt"))
+    }
+
+    /// Whether the currency codes are known, i.e. answers are checked and completed.
+    pub fn knows_currencies(&self) -> bool {
+        self.currencies.is_some()
+    }
+
+    /// The function `set_currencies` makes `codes` the currencies Tab completes and answers are checked against.
+    pub fn set_currencies(&mut self, codes: impl IntoIterator<Item = String>) {
+        let codes: BTreeSet<String> = codes.into_iter().map(|code| code.to_ascii_uppercase()).collect();
+        if let Some(helper) = self.editor.as_mut().and_then(|editor| editor.helper_mut()) {
+            helper.codes = codes.clone();
+        }
+        self.currencies = Some(codes);
+    }
+
+    /// The function `unknown` picks the codes out of `codes` that are not among the known currencies.
+    ///
+    /// Returns:
+    ///
+    /// The unknown codes in upper case, none while the currencies are not known.
+    pub fn unknown<'a>(&self, codes: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let Some(currencies) = &self.currencies else { return Vec::new() };
+        codes.into_iter()
+            .map(|code| code.trim().to_ascii_uppercase())
+            .filter(|code| !currencies.contains(code))
+            .collect()
+    }
+
+    /// The function `read` prints `prompt` and reads a line, adding it to the history.
+    ///
+    /// Returns:
+    ///
+    /// The line without surrounding whitespace, or `None` once stdin is closed, cannot be read
+    /// or Ctrl+C is pressed.
+    pub fn read(&mut self, prompt: &str) -> Option<String> {
+        let Some(editor) = self.editor.as_mut() else { return read_line(prompt) };
+        match editor.readline(&format!("{}: ", prompt)) {
+            Ok(line) => {
+                if let Some(path) = &self.history {
+                    save_history(editor, path);
+                }
+                Some(line.trim().to_string())
+            },
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => None,
+            Err(err) => {
+                warn!("Input could not be read: {}", err);
+                None
+            },
+        }
+    }
+}
+
+/// The function `save_history` appends the answers `editor` read since the last save to the
+/// history file at `path`, creating its directory first.
+fn save_history(editor: &mut Editor<CurrencyCompleter, FileHistory>, path: &Path) {
+    let saved = path.parent().map_or(Ok(()), std::fs::create_dir_all)
+        .map_err(ReadlineError::from)
+        .and_then(|()| editor.append_history(path));
+    if let Err(err) = saved {
+        debug!("History {} could not be saved: {}", path.display(), err);
+    }
+}
+
+/// The function `read_line` prints `prompt` and reads a line from stdin as it comes.
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{}: ", prompt);
+    io::stdout().flush().ok()?;
+    let mut input = String::new();
+    match io::stdin().lock().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustyline::history::{DefaultHistory, History, SearchDirection};
+
+    fn complete(line: &str) -> (usize, Vec<String>) {
+        let completer = CurrencyCompleter { codes: ["EUR", "GBP", "PLN", "USD", "UYU"].iter().map(|code| code.to_string()).collect() };
+        let history = DefaultHistory::new();
+        let (start, candidates) = completer.complete(line, line.len(), &Context::new(&history)).unwrap();
+        (start, candidates.into_iter().map(|pair| pair.replacement).collect())
+    }
+
+    #[test]
+    fn test_completes_the_code_after_whitespace() {
+        assert_eq!(complete("U"), (0, vec!["USD".to_string(), "UYU".to_string()]));
+        assert_eq!(complete("100 EUR  G"), (9, vec!["GBP".to_string()]));
+        assert_eq!(complete("100 "), (4, vec!["EUR".to_string(), "GBP".to_string(), "PLN".to_string(), "USD".to_string(), "UYU".to_string()]));
+    }
+
+    #[test]
+    fn test_completes_a_lowercase_code_after_a_comma() {
+        assert_eq!(complete("EUR,u"), (4, vec!["USD".to_string(), "UYU".to_string()]));
+        assert_eq!(complete("eur, gbp,pl"), (9, vec!["PLN".to_string()]));
+        assert_eq!(complete("EUR,x"), (4, Vec::new()));
+    }
+
+    #[test]
+    fn test_answers_are_appended_to_the_history_file() {
+        let dir = std::env::temp_dir().join(format!("task-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("task").join("history.txt");
+
+        let mut editor = Repl::editor(Some(&path)).unwrap();
+        editor.add_history_entry("EUR").unwrap();
+        editor.add_history_entry("100").unwrap();
+        save_history(&mut editor, &path);
+        let mut later = Repl::editor(Some(&path)).unwrap();
+        later.add_history_entry("PLN").unwrap();
+        save_history(&mut later, &path);
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(saved.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>(), vec!["EUR", "100", "PLN"]);
+        let history = Repl::editor(Some(&path)).unwrap();
+        assert_eq!(history.history().len(), 3);
+        assert_eq!(history.history().get(2, SearchDirection::Forward).unwrap().map(|entry| entry.entry.into_owned()), Some("PLN".to_string()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
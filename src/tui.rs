@@ -0,0 +1,799 @@
+//! The full-screen terminal UI of the `task` binary, started with `task tui`: an amount and two
+//! currency pickers whose converted result is kept up to date as they change, a rates table to
+//! scroll and filter, and a status bar with the provider, the age of the rates and the API quota.
+//! Like the menu, conversions are computed locally from one `latest` snapshot.
+
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use log::LevelFilter;
+use rust_decimal::Decimal;
+use task::api::arguments::RatiosListArguments;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::rate_table::RateTable;
+use task::api::usage::UsageReport;
+use task::api::{CurrencyBeaconClient, RateProvider};
+
+use crate::output::{Sort, SortKey};
+
+/// How often the screen is drawn while no key is pressed, so the age of the rates keeps ticking.
+const TICK: Duration = Duration::from_millis(250);
+
+/// How long to wait before fetching rates again after a failed attempt.
+const RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// The smallest terminal the UI is laid out in.
+const MIN_WIDTH: usize = 48;
+const MIN_HEIGHT: usize = 12;
+
+/// The width of the rate column of the tables.
+const RATE_WIDTH: usize = 16;
+
+/// Where the UI gets its rates from and how long they are good for.
+pub struct Source<'a> {
+    pub provider: &'a dyn RateProvider,
+    /// Tells how much of the API quota is used.
+    pub client: &'a CurrencyBeaconClient,
+    /// The base of the `latest` snapshot conversions are computed from.
+    pub base: &'a str,
+    /// Rates older than this are fetched again.
+    pub max_age: Duration,
+}
+
+/// The function `run` shows the UI until Esc or Ctrl+C is pressed, rows of tables ordered by `sort`.
+///
+/// Returns:
+///
+/// The IO error raised while drawing or reading keys, or when stdin or stdout is not a terminal.
+pub async fn run(source: Source<'_>, sort: Sort) -> io::Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "the terminal UI needs a terminal, use the subcommands from scripts"));
+    }
+    let mut screen = Screen::enter()?;
+    let mut app = App::new(source.base, sort);
+    app.message = Some("Fetching rates...".to_string());
+    screen.draw(&mut app)?;
+    match source.provider.currencies().await {
+        Ok(list) => app.names = list.currencies.into_iter().filter_map(|currency| Some((currency.code, currency.name?))).collect(),
+        Err(err) => app.message = Some(format!("Currency names could not be fetched: {}", err)),
+    }
+    let mut due = Instant::now();
+    loop {
+        if Instant::now() >= due {
+            app.message = Some("Fetching rates...".to_string());
+            screen.draw(&mut app)?;
+            due = Instant::now() + app.refresh(&source).await;
+        }
+        screen.draw(&mut app)?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            match app.handle(key) {
+                Step::Continue => {},
+                Step::Refresh => due = Instant::now(),
+                Step::Quit => return Ok(()),
+            }
+        }
+    }
+}
+
+/// The terminal while the UI owns it: raw mode on the alternate screen with the cursor hidden and
+/// the logger muted, as diagnostics would draw over the screen. Dropping it restores all of it.
+struct Screen {
+    out: io::Stdout,
+    log_level: LevelFilter,
+}
+
+impl Screen {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut screen = Screen { out: io::stdout(), log_level: log::max_level() };
+        log::set_max_level(LevelFilter::Off);
+        execute!(screen.out, EnterAlternateScreen, Hide)?;
+        Ok(screen)
+    }
+
+    /// The function `draw` lays `app` out on the whole terminal, overwriting every line in place.
+    fn draw(&mut self, app: &mut App) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let lines = app.render(usize::from(width), usize::from(height));
+        let mut out = self.out.lock();
+        for row in 0..height {
+            queue!(out, MoveTo(0, row))?;
+            let mut free = usize::from(width);
+            for span in lines.get(usize::from(row)).into_iter().flatten() {
+                let text = fit(&span.text, free);
+                free -= text.chars().count();
+                if span.highlight {
+                    queue!(out, SetAttribute(Attribute::Reverse), Print(text), SetAttribute(Attribute::NoReverse))?;
+                } else {
+                    queue!(out, Print(text))?;
+                }
+            }
+            queue!(out, Clear(ClearType::UntilNewLine))?;
+        }
+        out.flush()
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+        log::set_max_level(self.log_level);
+    }
+}
+
+/// A piece of a line on screen, drawn in reverse video when `highlight` is set.
+struct Span {
+    text: String,
+    highlight: bool,
+}
+
+type Line = Vec<Span>;
+
+fn plain(text: impl Into<String>) -> Span {
+    Span { text: text.into(), highlight: false }
+}
+
+fn highlighted(text: impl Into<String>) -> Span {
+    Span { text: text.into(), highlight: true }
+}
+
+/// A highlighted line spanning the whole `width`.
+fn bar(text: &str, width: usize) -> Line {
+    vec![highlighted(format!("{:<width$}", fit(text, width), width = width))]
+}
+
+/// The function `fit` cuts `text` down to at most `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// The function `scroll` keeps `selected` among `len` rows and moves `top`, the first of the
+/// `visible` rows shown, just far enough for the selected row to be shown.
+fn scroll(selected: &mut usize, top: &mut usize, len: usize, visible: usize) {
+    *selected = (*selected).min(len.saturating_sub(1));
+    if *selected < *top {
+        *top = *selected;
+    } else if *selected >= *top + visible {
+        *top = *selected + 1 - visible;
+    }
+    *top = (*top).min(len.saturating_sub(visible));
+}
+
+/// The function `age` tells how old something is, e.g. `42s`, `3m 05s` or `1h 02m`.
+fn age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// The function `rate` shows a rate to six decimal places without trailing zeros.
+fn rate(rate: Decimal) -> String {
+    rate.round_dp(6).normalize().to_string()
+}
+
+/// The part of the screen keys go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Amount,
+    From,
+    To,
+    Rates,
+}
+
+impl Focus {
+    fn next(self) -> Self {
+        match self {
+            Focus::Amount => Focus::From,
+            Focus::From => Focus::To,
+            Focus::To => Focus::Rates,
+            Focus::Rates => Focus::Amount,
+        }
+    }
+
+    fn previous(self) -> Self {
+        match self {
+            Focus::Amount => Focus::Rates,
+            Focus::From => Focus::Amount,
+            Focus::To => Focus::From,
+            Focus::Rates => Focus::To,
+        }
+    }
+}
+
+/// The currency picker, opened on `From` or `To`.
+struct Picker {
+    focus: Focus,
+    query: String,
+    selected: usize,
+    top: usize,
+}
+
+/// The `latest` snapshot conversions are computed from.
+struct Loaded {
+    table: RateTable,
+    provider: String,
+    fetched_at: Instant,
+    /// How old the answer already was when it was fetched, e.g. when served from the cache.
+    age: Duration,
+}
+
+/// A row of the rates table.
+struct Row {
+    code: String,
+    name: String,
+    rate: Decimal,
+}
+
+/// What the event loop does after a key.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Continue,
+    Refresh,
+    Quit,
+}
+
+/// Everything the UI shows. Keys change it, `render` turns it into lines.
+struct App {
+    focus: Focus,
+    amount: String,
+    from: String,
+    to: String,
+    loaded: Option<Loaded>,
+    /// Currency names by code, when the provider has them.
+    names: BTreeMap<String, String>,
+    usage: Option<UsageReport>,
+    filter: String,
+    selected: usize,
+    top: usize,
+    /// The number of table rows on screen, as of the last `render`.
+    page: usize,
+    picker: Option<Picker>,
+    /// The last error, or what the UI is busy with.
+    message: Option<String>,
+    sort: Sort,
+}
+
+impl App {
+    fn new(base: &str, sort: Sort) -> Self {
+        App {
+            focus: Focus::Amount,
+            amount: "100".to_string(),
+            from: base.to_ascii_uppercase(),
+            to: "PLN".to_string(),
+            loaded: None,
+            names: BTreeMap::new(),
+            usage: None,
+            filter: String::new(),
+            selected: 0,
+            top: 0,
+            page: 1,
+            picker: None,
+            message: None,
+            sort,
+        }
+    }
+
+    /// The function `refresh` fetches the `latest` snapshot of the base and the API usage.
+    ///
+    /// Returns:
+    ///
+    /// How long until the rates should be fetched again.
+    async fn refresh(&mut self, source: &Source<'_>) -> Duration {
+        let next = match source.provider.latest(RatiosListArguments { base: source.base.to_string() }).await {
+            Ok(rates) => {
+                self.message = None;
+                self.loaded = Some(Loaded {
+                    provider: rates.provider.clone(),
+                    fetched_at: Instant::now(),
+                    age: rates.cache.age,
+                    table: RateTable::from(rates),
+                });
+                source.max_age
+            },
+            Err(err) => {
+                self.message = Some(format!("Rates could not be fetched: {}", err));
+                RETRY_AFTER
+            },
+        };
+        self.usage = source.client.usage();
+        next
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Step {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') | KeyCode::Char('q') if ctrl => return Step::Quit,
+            KeyCode::Char('r') if ctrl => return Step::Refresh,
+            KeyCode::Char('s') if ctrl => {
+                std::mem::swap(&mut self.from, &mut self.to);
+                return Step::Continue;
+            },
+            _ if ctrl => return Step::Continue,
+            _ => {},
+        }
+        if let Some(picker) = self.picker.take() {
+            self.handle_picker(picker, key);
+            return Step::Continue;
+        }
+        match (self.focus, key.code) {
+            (_, KeyCode::Tab) => self.focus = self.focus.next(),
+            (_, KeyCode::BackTab) => self.focus = self.focus.previous(),
+            (Focus::Amount, KeyCode::Char(c)) if c.is_ascii_digit() || c == '.' || c == '-' => self.amount.push(c),
+            (Focus::Amount, KeyCode::Backspace) => {
+                self.amount.pop();
+            },
+            (Focus::From | Focus::To, KeyCode::Enter | KeyCode::Char(' ')) => self.open_picker(String::new()),
+            (Focus::From | Focus::To, KeyCode::Char(c)) if c.is_ascii_alphabetic() => self.open_picker(c.to_string()),
+            (Focus::Rates, KeyCode::Up) => self.selected = self.selected.saturating_sub(1),
+            (Focus::Rates, KeyCode::Down) => self.selected += 1,
+            (Focus::Rates, KeyCode::PageUp) => self.selected = self.selected.saturating_sub(self.page),
+            (Focus::Rates, KeyCode::PageDown) => self.selected += self.page,
+            (Focus::Rates, KeyCode::Home) => self.selected = 0,
+            (Focus::Rates, KeyCode::End) => self.selected = usize::MAX,
+            (Focus::Rates, KeyCode::Enter) => {
+                if let Some(row) = self.rows().1.into_iter().nth(self.selected) {
+                    self.to = row.code;
+                }
+            },
+            (Focus::Rates, KeyCode::Char(c)) => {
+                self.filter.push(c);
+                self.selected = 0;
+            },
+            (Focus::Rates, KeyCode::Backspace) => {
+                self.filter.pop();
+                self.selected = 0;
+            },
+            (Focus::Rates, KeyCode::Esc) if !self.filter.is_empty() => self.filter.clear(),
+            (_, KeyCode::Esc) => return Step::Quit,
+            _ => {},
+        }
+        Step::Continue
+    }
+
+    fn open_picker(&mut self, query: String) {
+        self.picker = Some(Picker { focus: self.focus, query, selected: 0, top: 0 });
+    }
+
+    /// The function `handle_picker` applies `key` to the open `picker`, which stays open unless
+    /// a currency was chosen or Esc pressed.
+    fn handle_picker(&mut self, mut picker: Picker, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Enter | KeyCode::Tab => {
+                if let Some(code) = self.candidates(&picker.query).into_iter().nth(picker.selected) {
+                    match picker.focus {
+                        Focus::From => self.from = code,
+                        _ => self.to = code,
+                    }
+                    self.focus = self.focus.next();
+                    return;
+                }
+            },
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Down => picker.selected += 1,
+            KeyCode::PageUp => picker.selected = picker.selected.saturating_sub(self.page),
+            KeyCode::PageDown => picker.selected += self.page,
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.selected = 0;
+            },
+            KeyCode::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+            },
+            _ => {},
+        }
+        let len = self.candidates(&picker.query).len();
+        picker.selected = picker.selected.min(len.saturating_sub(1));
+        self.picker = Some(picker);
+    }
+
+    /// The function `candidates` lists the currencies whose code starts with `query` or whose
+    /// name contains it, ignoring case.
+    fn candidates(&self, query: &str) -> Vec<String> {
+        let codes: Vec<String> = match &self.loaded {
+            Some(loaded) => loaded.table.currencies(),
+            None => self.names.keys().cloned().collect(),
+        };
+        let query = query.to_ascii_lowercase();
+        codes.into_iter()
+            .filter(|code| code.to_ascii_lowercase().starts_with(&query)
+                || self.names.get(code).is_some_and(|name| name.to_ascii_lowercase().contains(&query)))
+            .collect()
+    }
+
+    /// The function `rows` quotes every currency against `from`, or against the base of the
+    /// snapshot when it does not list `from`, keeping the rows that match the filter.
+    ///
+    /// Returns:
+    ///
+    /// The currency the rates are quoted against and the rows ordered by `sort`.
+    fn rows(&self) -> (String, Vec<Row>) {
+        let Some(loaded) = &self.loaded else { return (self.from.clone(), Vec::new()) };
+        let table = &loaded.table;
+        let base = match table.rate(&self.from, &self.from) {
+            Ok(_) => self.from.clone(),
+            Err(_) => table.snapshot().base.clone(),
+        };
+        let filter = self.filter.to_ascii_lowercase();
+        let mut rows: Vec<Row> = table.currencies().into_iter()
+            .filter(|code| *code != base)
+            .filter_map(|code| {
+                let (rate, _) = table.rate(&base, &code).ok()?;
+                Some(Row { name: self.names.get(&code).cloned().unwrap_or_default(), code, rate })
+            })
+            .filter(|row| row.code.to_ascii_lowercase().contains(&filter) || row.name.to_ascii_lowercase().contains(&filter))
+            .collect();
+        self.sort.apply(&mut rows, |a, b| match self.sort.key {
+            SortKey::Code => a.code.cmp(&b.code),
+            SortKey::Name => a.name.cmp(&b.name).then_with(|| a.code.cmp(&b.code)),
+            SortKey::Rate => a.rate.cmp(&b.rate).then_with(|| a.code.cmp(&b.code)),
+        });
+        (base, rows)
+    }
+
+    /// The converted amount, or why there is none.
+    fn result(&self) -> String {
+        if self.amount.is_empty() {
+            return "Type an amount to convert".to_string();
+        }
+        let Ok(amount) = self.amount.parse::<Decimal>() else {
+            return "Amount must be number".to_string();
+        };
+        let Some(loaded) = &self.loaded else {
+            return "No rates yet".to_string();
+        };
+        match loaded.table.convert(&Money::new(amount, &self.from), &self.to, RoundingPolicy::default()) {
+            Ok(conversion) => format!("{} = {}    rate {} via {}", conversion.from, conversion.to, rate(conversion.rate), conversion.path),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn status(&self) -> String {
+        let rates = match &self.loaded {
+            Some(loaded) => format!("{} rates of {}, {} old", loaded.provider, loaded.table.snapshot().date, age(loaded.age + loaded.fetched_at.elapsed())),
+            None => "no rates".to_string(),
+        };
+        let quota = match &self.usage {
+            Some(report) => match (report.allowance, report.used_fraction()) {
+                (Some(allowance), Some(used)) => format!("quota {}/{} ({:.1}%)", report.total, allowance, used * 100.0),
+                _ => format!("{} requests this month", report.total),
+            },
+            None => "quota not tracked".to_string(),
+        };
+        match &self.message {
+            Some(message) => format!(" {} | {} | {}", rates, quota, message),
+            None => format!(" {} | {}", rates, quota),
+        }
+    }
+
+    /// An input field, highlighted when it has the focus.
+    fn field(&self, focus: Focus, text: &str, width: usize) -> Span {
+        if self.focus == focus && self.picker.is_none() {
+            highlighted(format!(" {:<width$} ", text, width = width))
+        } else {
+            plain(format!("[{:<width$}]", text, width = width))
+        }
+    }
+
+    /// The function `render` lays the UI out on a `width` by `height` screen, moving the table
+    /// and the picker so their selected rows are visible.
+    fn render(&mut self, width: usize, height: usize) -> Vec<Line> {
+        if width < MIN_WIDTH || height < MIN_HEIGHT {
+            return vec![vec![plain(format!("Enlarge the terminal to {}x{}, Esc quits", MIN_WIDTH, MIN_HEIGHT))]];
+        }
+        let mut lines: Vec<Line> = vec![
+            bar(" task: exchange rates", width),
+            Vec::new(),
+            vec![
+                plain("  Amount "),
+                self.field(Focus::Amount, &self.amount, 14),
+                plain("   From "),
+                self.field(Focus::From, &self.from, 3),
+                plain("   To "),
+                self.field(Focus::To, &self.to, 3),
+            ],
+            Vec::new(),
+            vec![plain(format!("  {}", self.result()))],
+            Vec::new(),
+        ];
+        let visible = height - lines.len() - 4;
+        self.page = visible;
+        let name_width = width.saturating_sub(RATE_WIDTH + 12);
+        let row = |code: &str, name: &str, rate: &str| {
+            format!("  {:<4}  {:<name_width$}  {:>rate_width$}  ", code, fit(name, name_width), rate, name_width = name_width, rate_width = RATE_WIDTH)
+        };
+        if let Some(mut picker) = self.picker.take() {
+            let candidates = self.candidates(&picker.query);
+            scroll(&mut picker.selected, &mut picker.top, candidates.len(), visible);
+            let side = if picker.focus == Focus::From { "to convert from" } else { "to convert into" };
+            lines.push(vec![plain(format!("  Currency {}: ", side)), highlighted(format!("{}_", picker.query))]);
+            lines.push(vec![plain(row("CODE", "NAME", ""))]);
+            for (index, code) in candidates.iter().enumerate().skip(picker.top).take(visible) {
+                let text = row(code, self.names.get(code).map_or("", String::as_str), "");
+                lines.push(vec![if index == picker.selected { highlighted(text) } else { plain(text) }]);
+            }
+            self.picker = Some(picker);
+        } else {
+            let (base, rows) = self.rows();
+            scroll(&mut self.selected, &mut self.top, rows.len(), visible);
+            let date = self.loaded.as_ref().map(|loaded| loaded.table.snapshot().date.clone()).unwrap_or_default();
+            let filter = if self.focus == Focus::Rates { highlighted(format!("{}_", self.filter)) } else { plain(self.filter.clone()) };
+            lines.push(vec![plain(format!("  Rates of 1 {} on {}, {} shown   Filter: ", base, date, rows.len())), filter]);
+            lines.push(vec![plain(row("CODE", "NAME", "RATE"))]);
+            for (index, quoted) in rows.iter().enumerate().skip(self.top).take(visible) {
+                let text = row(&quoted.code, &quoted.name, &rate(quoted.rate));
+                lines.push(vec![if self.focus == Focus::Rates && index == self.selected { highlighted(text) } else { plain(text) }]);
+            }
+        }
+        lines.resize_with(height - 2, Vec::new);
+        lines.push(bar(&self.status(), width));
+        let keys = match (&self.picker, self.focus) {
+            (Some(_), _) => " Type to search  Up/Down move  Enter choose  Esc cancel",
+            (None, Focus::Rates) => " Type to filter  Up/Down/PgUp/PgDn scroll  Enter convert into  Tab next  ^S swap  ^R refresh  Esc quit",
+            (None, Focus::From | Focus::To) => " Enter or type to pick  Tab next  ^S swap  ^R refresh  Esc quit",
+            (None, Focus::Amount) => " Type the amount  Tab next  ^S swap  ^R refresh  Esc quit",
+        };
+        lines.push(vec![plain(keys)]);
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+    use task::api::cache::CacheInfo;
+    use task::api::provider::Rates;
+
+    /// An app with rates against USD and the names of their currencies, as after the first refresh.
+    fn app() -> App {
+        let mut app = App::new("usd", Sort::default());
+        app.loaded = Some(Loaded {
+            table: RateTable::from(Rates {
+                provider: "currencybeacon".to_string(),
+                base: "USD".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(),
+                rates: BTreeMap::from([
+                    ("CAD".to_string(), dec!(1.3487)),
+                    ("EUR".to_string(), dec!(0.9146)),
+                    ("GBP".to_string(), dec!(0.7812)),
+                    ("JPY".to_string(), dec!(147.06)),
+                    ("PLN".to_string(), dec!(3.9512)),
+                ]),
+                cache: CacheInfo::default(),
+            }),
+            provider: "currencybeacon".to_string(),
+            fetched_at: Instant::now(),
+            age: Duration::ZERO,
+        });
+        app.names = [("CAD", "Canadian Dollar"), ("EUR", "Euro"), ("GBP", "British Pound"), ("JPY", "Japanese Yen"), ("PLN", "Polish Zloty"), ("USD", "US Dollar")]
+            .into_iter()
+            .map(|(code, name)| (code.to_string(), name.to_string()))
+            .collect();
+        app
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            assert_eq!(app.handle(key(KeyCode::Char(c))), Step::Continue);
+        }
+    }
+
+    fn codes(app: &App) -> Vec<String> {
+        app.rows().1.into_iter().map(|row| row.code).collect()
+    }
+
+    /// The text of every line, highlighted spans wrapped in `[` and `]`.
+    fn screen(app: &mut App, width: usize, height: usize) -> Vec<String> {
+        app.render(width, height).iter()
+            .map(|line| line.iter().map(|span| if span.highlight { format!("[{}]", span.text.trim_end()) } else { span.text.clone() }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_tab_cycles_the_focus_both_ways() {
+        let mut app = app();
+        let mut seen = vec![app.focus];
+        for _ in 0..4 {
+            app.handle(key(KeyCode::Tab));
+            seen.push(app.focus);
+        }
+        assert_eq!(seen, vec![Focus::Amount, Focus::From, Focus::To, Focus::Rates, Focus::Amount]);
+        app.handle(key(KeyCode::BackTab));
+        assert_eq!(app.focus, Focus::Rates);
+        app.handle(key(KeyCode::BackTab));
+        assert_eq!(app.focus, Focus::To);
+    }
+
+    #[test]
+    fn test_amount_takes_numbers_and_converts_live() {
+        let mut app = app();
+        assert_eq!(app.result(), "100 USD = 395.12 PLN    rate 3.9512 via USD -> PLN");
+        app.handle(key(KeyCode::Backspace));
+        app.handle(key(KeyCode::Backspace));
+        press(&mut app, "x2.5");
+        assert_eq!(app.amount, "12.5");
+        assert_eq!(app.result(), "12.5 USD = 49.39 PLN    rate 3.9512 via USD -> PLN");
+        press(&mut app, "..");
+        assert_eq!(app.result(), "Amount must be number");
+        app.amount.clear();
+        assert_eq!(app.result(), "Type an amount to convert");
+    }
+
+    #[test]
+    fn test_picker_filters_by_code_or_name_and_picks() {
+        let mut app = app();
+        app.handle(key(KeyCode::Tab));
+        press(&mut app, "p");
+        assert_eq!(app.picker.as_ref().map(|picker| picker.query.as_str()), Some("p"));
+        assert_eq!(app.candidates("p"), vec!["GBP", "JPY", "PLN"], "PLN by its code, the others by their names");
+        press(&mut app, "ound");
+        assert_eq!(app.candidates("pound"), vec!["GBP"]);
+        app.handle(key(KeyCode::Enter));
+        assert!(app.picker.is_none());
+        assert_eq!(app.from, "GBP");
+        assert_eq!(app.focus, Focus::To);
+
+        app.handle(key(KeyCode::Enter));
+        assert_eq!(app.candidates(""), vec!["CAD", "EUR", "GBP", "JPY", "PLN", "USD"]);
+        app.handle(key(KeyCode::Down));
+        app.handle(key(KeyCode::Down));
+        app.handle(key(KeyCode::Up));
+        app.handle(key(KeyCode::Enter));
+        assert_eq!(app.to, "EUR");
+        assert_eq!(app.focus, Focus::Rates);
+    }
+
+    #[test]
+    fn test_picker_keeps_the_selection_among_the_candidates_and_esc_cancels() {
+        let mut app = app();
+        app.handle(key(KeyCode::BackTab));
+        app.handle(key(KeyCode::BackTab));
+        press(&mut app, "e");
+        for _ in 0..5 {
+            app.handle(key(KeyCode::Down));
+        }
+        assert_eq!(app.picker.as_ref().map(|picker| picker.selected), Some(1), "only EUR and JPY match");
+        press(&mut app, "x");
+        app.handle(key(KeyCode::Enter));
+        assert!(app.picker.is_some(), "nothing to pick, the picker stays open");
+        app.handle(key(KeyCode::Esc));
+        assert!(app.picker.is_none());
+        assert_eq!((app.from.as_str(), app.to.as_str(), app.focus), ("USD", "PLN", Focus::To));
+    }
+
+    #[test]
+    fn test_rates_filter_matches_codes_and_names() {
+        let mut app = app();
+        app.handle(key(KeyCode::BackTab));
+        assert_eq!(codes(&app), vec!["CAD", "EUR", "GBP", "JPY", "PLN"]);
+        press(&mut app, "yen");
+        assert_eq!(codes(&app), vec!["JPY"]);
+        app.handle(key(KeyCode::Backspace));
+        app.handle(key(KeyCode::Backspace));
+        assert_eq!(codes(&app), vec!["JPY", "PLN"]);
+        app.handle(key(KeyCode::Backspace));
+        press(&mut app, "P");
+        assert_eq!(codes(&app), vec!["GBP", "JPY", "PLN"]);
+        assert_eq!(app.handle(key(KeyCode::Esc)), Step::Continue, "Esc clears the filter first");
+        assert_eq!(codes(&app).len(), 5);
+        assert_eq!(app.handle(key(KeyCode::Esc)), Step::Quit);
+    }
+
+    #[test]
+    fn test_rates_are_quoted_against_from_and_enter_converts_into_the_row() {
+        let mut app = app();
+        app.from = "PLN".to_string();
+        let (base, rows) = app.rows();
+        assert_eq!(base, "PLN");
+        assert!(rows.iter().any(|row| row.code == "USD"));
+        app.from = "XD".to_string();
+        assert_eq!(app.rows().0, "USD", "an unknown currency is quoted against the base");
+
+        app.from = "USD".to_string();
+        app.handle(key(KeyCode::BackTab));
+        app.handle(key(KeyCode::Down));
+        app.handle(key(KeyCode::Enter));
+        assert_eq!(app.to, "EUR");
+    }
+
+    #[test]
+    fn test_ctrl_keys_swap_refresh_and_quit() {
+        let mut app = app();
+        assert_eq!(app.handle(ctrl('s')), Step::Continue);
+        assert_eq!((app.from.as_str(), app.to.as_str()), ("PLN", "USD"));
+        assert_eq!(app.handle(ctrl('x')), Step::Continue);
+        assert_eq!(app.amount, "100");
+        assert_eq!(app.handle(ctrl('r')), Step::Refresh);
+        assert_eq!(app.handle(ctrl('q')), Step::Quit);
+        assert_eq!(app.handle(ctrl('c')), Step::Quit);
+    }
+
+    #[test]
+    fn test_render_scrolls_the_selected_row_into_view() {
+        let mut app = app();
+        app.handle(key(KeyCode::BackTab));
+        let first = screen(&mut app, 60, 12);
+        assert_eq!(app.page, 2);
+        assert!(first[8].starts_with("[  CAD"), "{:?}", first);
+        assert!(first[9].starts_with("  EUR"));
+
+        for _ in 0..3 {
+            app.handle(key(KeyCode::Down));
+        }
+        let lines = screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (3, 2));
+        assert!(lines[8].starts_with("  GBP") && lines[9].starts_with("[  JPY"), "{:?}", lines);
+
+        app.handle(key(KeyCode::End));
+        screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (4, 3));
+        app.handle(key(KeyCode::PageDown));
+        screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (4, 3));
+        app.handle(key(KeyCode::PageUp));
+        screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (2, 2));
+        app.handle(key(KeyCode::Home));
+        screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (0, 0));
+
+        press(&mut app, "zz");
+        let empty = screen(&mut app, 60, 12);
+        assert_eq!((app.selected, app.top), (0, 0));
+        assert!(empty[8].is_empty());
+    }
+
+    #[test]
+    fn test_scroll_bounds() {
+        let (mut selected, mut top) = (9, 0);
+        scroll(&mut selected, &mut top, 5, 3);
+        assert_eq!((selected, top), (4, 2));
+        scroll(&mut selected, &mut top, 10, 3);
+        assert_eq!((selected, top), (4, 2));
+        selected = 1;
+        scroll(&mut selected, &mut top, 10, 3);
+        assert_eq!((selected, top), (1, 1));
+        scroll(&mut selected, &mut top, 0, 3);
+        assert_eq!((selected, top), (0, 0));
+        let (mut selected, mut top) = (2, 7);
+        scroll(&mut selected, &mut top, 4, 10);
+        assert_eq!((selected, top), (2, 0));
+    }
+
+    #[test]
+    fn test_render_lays_out_the_status_bar_and_small_terminals() {
+        let mut app = app();
+        let lines = screen(&mut app, 80, 14);
+        assert_eq!(lines.len(), 14);
+        assert!(lines[12].starts_with("[ currencybeacon rates of 2024-03-09, 0s old | quota not tracked"), "{:?}", lines[12]);
+        assert!(lines[2].contains("[ 100") && lines[2].contains("[USD]") && lines[2].contains("[PLN]"), "{:?}", lines[2]);
+        assert_eq!(screen(&mut app, 40, 14), vec!["Enlarge the terminal to 48x12, Esc quits".to_string()]);
+    }
+}
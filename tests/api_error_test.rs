@@ -0,0 +1,39 @@
+use std::error::Error;
+use reqwest::StatusCode;
+use task::api::ApiError;
+
+#[test]
+fn test_api_error_success_response_is_not_an_error() {
+      let body = r#"{"meta":{"code":200},"response":{"base":"USD","rates":{}}}"#;
+      assert!(ApiError::from_response(StatusCode::OK, body, None).is_none());
+}
+
+#[test]
+fn test_api_error_unauthorized_keeps_detail() {
+      let body = r#"{"meta":{"code":401,"error_type":"auth failed","error_detail":"Invalid API key"},"response":[]}"#;
+      let err = ApiError::from_response(StatusCode::UNAUTHORIZED, body, None).unwrap();
+      assert!(matches!(&err, ApiError::Unauthorized { detail: Some(detail), .. } if detail == "Invalid API key"));
+      assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+}
+
+#[test]
+fn test_api_error_meta_code_overrides_successful_status() {
+      let body = r#"{"meta":{"code":429,"error_type":"quota","error_detail":"Monthly limit reached"},"response":[]}"#;
+      let err = ApiError::from_response(StatusCode::OK, body, None).unwrap();
+      assert!(matches!(err, ApiError::QuotaExceeded { .. }));
+}
+
+#[test]
+fn test_api_error_server_error_without_payload() {
+      let err = ApiError::from_response(StatusCode::BAD_GATEWAY, "<html>Bad gateway</html>", None).unwrap();
+      assert!(matches!(err, ApiError::Status { status: StatusCode::BAD_GATEWAY, detail: None, .. }));
+      assert_eq!(err.to_string(), "API answered with 502 Bad Gateway");
+}
+
+#[test]
+fn test_api_error_malformed_payload_keeps_source() {
+      let body = "{\"meta\":".to_string();
+      let source = serde_json::from_str::<serde_json::Value>(&body).unwrap_err();
+      let err = ApiError::MalformedPayload { body, source };
+      assert!(err.source().is_some());
+}
@@ -0,0 +1,72 @@
+use std::time::{Duration, SystemTime};
+use task::api::cache::{CacheBackend, CacheConfig, CacheEntry, CacheInfo, CacheTtl, DiskCache, MemoryCache, ResponseCache};
+
+fn entry(body: &str, age: Duration) -> CacheEntry {
+      CacheEntry{ body: body.to_string(), stored_at: SystemTime::now() - age }
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+      std::env::temp_dir().join(format!("task-cache-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn test_cache_key_is_normalized_and_drops_api_key() {
+      let first = ResponseCache::key("/v1/historical", &[("api_key", "secret".to_string()), ("symbols", "pln, eur".to_string()), ("base", "usd".to_string())]);
+      let second = ResponseCache::key("/v1/historical", &[("base", "USD".to_string()), ("symbols", "EUR,PLN".to_string())]);
+      assert_eq!(first, second);
+      assert_eq!(first, "/v1/historical?base=USD&symbols=EUR,PLN");
+}
+
+#[test]
+fn test_memory_cache_evicts_least_recently_used() {
+      let cache = MemoryCache::new(2);
+      cache.put("a", entry("1", Duration::ZERO));
+      cache.put("b", entry("2", Duration::ZERO));
+      assert!(cache.get("a").is_some());
+      cache.put("c", entry("3", Duration::ZERO));
+      assert_eq!(cache.len(), 2);
+      assert!(cache.get("b").is_none());
+      assert_eq!(cache.get("a").unwrap().body, "1");
+      assert_eq!(cache.get("c").unwrap().body, "3");
+}
+
+#[test]
+fn test_response_cache_respects_ttl_per_endpoint() {
+      let cache = ResponseCache::new(CacheConfig{
+            ttl: CacheTtl{ latest: Duration::from_secs(60), convert: Duration::ZERO, ..CacheTtl::default() },
+            ..CacheConfig::default()
+      }).unwrap();
+      cache.put("/v1/latest", "/v1/latest?base=USD", "{}");
+      cache.put("/v1/convert", "/v1/convert?from=USD", "{}");
+      let hit = cache.get("/v1/latest", "/v1/latest?base=USD").unwrap();
+      assert!(hit.age() < Duration::from_secs(60));
+      assert!(cache.get("/v1/convert", "/v1/convert?from=USD").is_none());
+}
+
+#[test]
+fn test_response_cache_expired_entry_is_a_miss() {
+      let dir = temp_dir("expired");
+      let disk = DiskCache::new(&dir).unwrap();
+      disk.put("/v1/latest?base=USD", entry("{}", Duration::from_secs(120)));
+      let cache = ResponseCache::new(CacheConfig{ disk_dir: Some(dir.clone()), ..CacheConfig::default() }).unwrap();
+      assert!(cache.get("/v1/latest", "/v1/latest?base=USD").is_none());
+      std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_disk_cache_survives_a_new_cache_instance() {
+      let dir = temp_dir("persist");
+      let config = CacheConfig{ disk_dir: Some(dir.clone()), ..CacheConfig::default() };
+      ResponseCache::new(config.clone()).unwrap().put("/v1/currencies", "/v1/currencies?", "{\"meta\":{\"code\":200}}");
+      let reopened = ResponseCache::new(config).unwrap();
+      assert_eq!(reopened.get("/v1/currencies", "/v1/currencies?").unwrap().body, "{\"meta\":{\"code\":200}}");
+      std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cache_info_merge() {
+      let fresh = CacheInfo::default();
+      let cached = CacheInfo{ from_cache: true, age: Duration::from_secs(30) };
+      assert_eq!(cached.merge(fresh), CacheInfo{ from_cache: false, age: Duration::from_secs(30) });
+      assert!(cached.merge(cached).from_cache);
+}
@@ -0,0 +1,170 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments, TimeSeriesArguments};
+use task::api::cassette::{Cassette, CassetteConfig, CassetteMode};
+use task::api::credentials::Credentials;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::retry::RetryPolicy;
+use task::api::{
+      ApiError, ClientConfig, CurrencyBeaconClient, CurrencyListRequest, ExchangeCurrencyRequest, HistoricalRatesRequest,
+      RatiosListRequest, Requests, TimeSeriesRequest,
+};
+use task::mock::{Fault, FaultProfile, MockConfig, MockServer, MOCK_API_KEY};
+
+fn date(day: u32) -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+}
+
+fn temp_cassette(name: &str) -> PathBuf {
+      std::env::temp_dir().join(format!("task-cassette-{}-{}.json", name, std::process::id()))
+}
+
+/// Nothing listens on port 1, so any request that is not replayed fails.
+fn offline_credentials() -> Credentials {
+      Credentials{ url: "http://127.0.0.1:1".to_string(), api_key: "not-the-recorded-key".to_string() }
+}
+
+fn replaying(path: PathBuf) -> CurrencyBeaconClient {
+      let config = ClientConfig{ cassette: Some(CassetteConfig::replay(path)), retry: RetryPolicy::none(), ..ClientConfig::default() };
+      CurrencyBeaconClient::with_config(&offline_credentials(), config).unwrap()
+}
+
+fn bundled() -> CurrencyBeaconClient {
+      replaying(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/cassettes/requests.json"))
+}
+
+fn convert(to: &str, date: Option<NaiveDate>) -> ExchangeCurrencyArguments {
+      ExchangeCurrencyArguments{ from: "USD".to_string(), to: to.to_string(), amount: dec!(100), date, rounding: RoundingPolicy::default() }
+}
+
+#[tokio::test]
+async fn test_recording_strips_the_api_key_and_replays_offline() {
+      let path = temp_cassette("round-trip");
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let recording = CurrencyBeaconClient::with_config(&server.credentials(), ClientConfig{
+            cassette: Some(CassetteConfig::record(&path)),
+            ..ClientConfig::default()
+      }).unwrap();
+      let recorded = (ExchangeCurrencyRequest{ client: &recording, args: convert("PLN", None) }).call().await.unwrap();
+      drop(server);
+
+      let contents = std::fs::read_to_string(&path).unwrap();
+      assert!(!contents.contains(MOCK_API_KEY));
+      assert!(!contents.contains("api_key"));
+      let interactions = recording.cassette().unwrap().interactions();
+      assert_eq!(interactions.len(), 1);
+      assert_eq!(interactions[0].endpoint, "/v1/convert");
+      assert_eq!(interactions[0].params["amount"], "100");
+
+      let replayed = (ExchangeCurrencyRequest{ client: &replaying(path.clone()), args: convert("PLN", None) }).call().await.unwrap();
+      assert_eq!(replayed.converted(), recorded.converted());
+      std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_retried_responses_replay_in_recorded_order() {
+      let path = temp_cassette("retries");
+      let server = MockServer::start(MockConfig{ faults: FaultProfile::none().on_request(1, Fault::Status(500)), ..MockConfig::default() }).await.unwrap();
+      let retry = RetryPolicy{ base_delay: Duration::from_millis(1), jitter: 0.0, ..RetryPolicy::default() };
+      let recording = CurrencyBeaconClient::with_config(&server.credentials(), ClientConfig{
+            cassette: Some(CassetteConfig::record(&path)),
+            retry: retry.clone(),
+            ..ClientConfig::default()
+      }).unwrap();
+      (RatiosListRequest{ client: &recording, args: RatiosListArguments{ base: "USD".to_string() } }).call().await.unwrap();
+      let statuses: Vec<u16> = recording.cassette().unwrap().interactions().iter().map(|interaction| interaction.status).collect();
+      assert_eq!(statuses, vec![500, 200]);
+
+      let client = replaying(path.clone());
+      let args = RatiosListArguments{ base: "USD".to_string() };
+      let failed = (RatiosListRequest{ client: &client, args: args.clone() }).call().await.unwrap_err();
+      assert!(matches!(failed, ApiError::Status{ .. }));
+      let latest = (RatiosListRequest{ client: &client, args: args.clone() }).call().await.unwrap();
+      assert_eq!(latest.rates["PLN"], dec!(3.9512));
+      // Used up: the last recorded response keeps being served.
+      assert!((RatiosListRequest{ client: &client, args }).call().await.is_ok());
+      std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_unrecorded_request_is_invalid() {
+      let err = (RatiosListRequest{ client: &bundled(), args: RatiosListArguments{ base: "GBP".to_string() } }).call().await.unwrap_err();
+      match err {
+            ApiError::InvalidArguments{ reason, .. } => assert!(reason.contains("/v1/latest"), "{}", reason),
+            other => panic!("expected invalid arguments, got {:?}", other),
+      }
+}
+
+#[test]
+fn test_missing_cassette_cannot_be_replayed() {
+      let config = ClientConfig{ cassette: Some(CassetteConfig::replay(temp_cassette("missing"))), ..ClientConfig::default() };
+      let err = CurrencyBeaconClient::with_config(&offline_credentials(), config).unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ .. }));
+}
+
+#[test]
+fn test_cassette_matches_on_endpoint_and_params() {
+      let path = temp_cassette("matching");
+      let cassette = Cassette::open(CassetteConfig::record(&path)).unwrap();
+      assert_eq!(cassette.mode(), CassetteMode::Record);
+      cassette.record("/v1/latest", &[("api_key", "secret".to_string()), ("base", "USD".to_string())], 200, None, r#"{"response":1}"#);
+      cassette.record("/v1/latest", &[("base", "EUR".to_string())], 429, Some(Duration::from_secs(3)), "slow down");
+
+      let cassette = Cassette::open(CassetteConfig::replay(&path)).unwrap();
+      let usd = cassette.replay("/v1/latest", &[("base", "USD".to_string()), ("api_key", "other".to_string())]).unwrap();
+      assert_eq!(usd.body_text(), r#"{"response":1}"#);
+      assert!(!usd.params.contains_key("api_key"));
+      let eur = cassette.replay("/v1/latest", &[("base", "EUR".to_string())]).unwrap();
+      assert_eq!((eur.status, eur.retry_after, eur.body_text()), (429, Some(3), "slow down".to_string()));
+      assert!(cassette.replay("/v1/latest", &[]).is_none());
+      assert!(cassette.replay("/v1/convert", &[("base", "USD".to_string())]).is_none());
+      std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_bundled_cassette_serves_latest_and_unknown_bases() {
+      let client = &bundled();
+      let latest = (RatiosListRequest{ client, args: RatiosListArguments{ base: "usd".to_string() } }).call().await.unwrap();
+      assert_eq!(latest.base, "USD");
+      assert_eq!(latest.rates["EUR"], dec!(0.9146));
+      assert!(!latest.cache.from_cache);
+      let unknown = (RatiosListRequest{ client, args: RatiosListArguments{ base: "XYZ".to_string() } }).call().await.unwrap_err();
+      assert!(matches!(unknown, ApiError::UnknownCurrency{ .. }));
+}
+
+#[tokio::test]
+async fn test_bundled_cassette_serves_conversions() {
+      let client = &bundled();
+      let current = (ExchangeCurrencyRequest{ client, args: convert("PLN", None) }).call().await.unwrap();
+      assert_eq!(current.converted(), Some(Money::new(dec!(395.12), "PLN")));
+      let dated = (ExchangeCurrencyRequest{ client, args: convert("PLN", Some(date(1))) }).call().await.unwrap();
+      assert_eq!(dated.converted(), Some(Money::new(dec!(398.97), "PLN")));
+      let unknown = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{ amount: dec!(1), ..convert("XD", None) } }).call().await.unwrap_err();
+      assert!(matches!(unknown, ApiError::UnknownCurrency{ .. }));
+}
+
+#[tokio::test]
+async fn test_bundled_cassette_serves_historical_timeseries_and_currencies() {
+      let client = &bundled();
+      let historical = (HistoricalRatesRequest{ client, args: HistoricalRatesArguments{ date: date(5), base: "EUR".to_string(), symbols: None } }).call().await.unwrap();
+      assert_eq!(historical.date, date(5));
+      assert!(historical.rates.contains_key("USD"));
+
+      let series = (TimeSeriesRequest{ client, args: TimeSeriesArguments{
+            start_date: date(1),
+            end_date: date(6),
+            base: "USD".to_string(),
+            symbols: Some(vec!["PLN".to_string(), "EUR".to_string()]),
+      } }).call().await.unwrap();
+      assert_eq!(series.rates.len(), 4);
+      assert_eq!(series.rates[&date(5)]["PLN"], dec!(3.9780));
+
+      let list = (CurrencyListRequest{ client }).call().await.unwrap();
+      assert!(list.currencies.iter().any(|currency| currency.short_code == "PLN"));
+
+      let health = client.health().await.unwrap();
+      assert!(!health.reachable, "the bundled cassette holds no health check");
+}
@@ -0,0 +1,259 @@
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+
+use task::api::credentials::Credentials;
+use task::mock::{Fixtures, MockConfig, MockServer};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Runs the `task` binary against `credentials` with `input` on stdin, keeping its config and
+/// state in a directory of its own.
+async fn interact(name: &str, credentials: &Credentials, args: &[&str], input: &str) -> Output {
+      let home: PathBuf = std::env::temp_dir().join(format!("task-cli-{}-{}", name, std::process::id()));
+      let mut child = Command::new(env!("CARGO_BIN_EXE_task"))
+            .args(args)
+            .env("API_URL", &credentials.url)
+            .env("API_KEY", &credentials.api_key)
+            .env("XDG_CONFIG_HOME", home.join("config"))
+            .env("XDG_DATA_HOME", home.join("data"))
+            .env_remove("ECB_RATES")
+            .env_remove("API_RECORD_CASSETTE")
+            .env_remove("API_REPLAY_CASSETTE")
+            .env_remove("RUST_LOG")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+      let mut stdin = child.stdin.take().unwrap();
+      stdin.write_all(input.as_bytes()).await.unwrap();
+      drop(stdin);
+      let output = child.wait_with_output().await.unwrap();
+      let _ = std::fs::remove_dir_all(home);
+      output
+}
+
+async fn task(name: &str, credentials: &Credentials, args: &[&str]) -> Output {
+      interact(name, credentials, args, "").await
+}
+
+fn stdout(output: &Output) -> String {
+      String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+      String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[tokio::test]
+async fn test_convert_prints_the_converted_amount() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("convert", &server.credentials(), &["convert", "100", "usd", "pln"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      assert_eq!(stdout(&output), "395.12\n");
+
+      let dated = task("convert-dated", &server.credentials(), &["convert", "100", "USD", "PLN", "--date", "2024-03-01"]).await;
+      assert_eq!(dated.status.code(), Some(0));
+      assert_eq!(stdout(&dated), "398.97\n");
+}
+
+#[tokio::test]
+async fn test_rates_prints_one_line_per_symbol() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("rates", &server.credentials(), &["rates", "--base", "USD", "--symbols", "pln,EUR"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      assert_eq!(stdout(&output), "EUR:0.9146\nPLN:3.9512\n");
+
+      let dated = task("rates-dated", &server.credentials(), &["rates", "--symbols", "PLN", "--date", "2024-03-05"]).await;
+      assert_eq!(dated.status.code(), Some(0));
+      assert_eq!(stdout(&dated), "PLN:3.9780\n");
+}
+
+#[tokio::test]
+async fn test_currencies_and_health() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let currencies = task("currencies", &server.credentials(), &["currencies"]).await;
+      assert_eq!(currencies.status.code(), Some(0));
+      assert!(stdout(&currencies).lines().any(|line| line.starts_with("PLN:")));
+
+      let health = task("health", &server.credentials(), &["health"]).await;
+      assert_eq!(health.status.code(), Some(0));
+      assert_eq!(stdout(&health), "200\n");
+}
+
+#[tokio::test]
+async fn test_usage_errors_exit_with_2() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      for args in [&["convert", "abc", "USD", "PLN"][..], &["convert", "100", "USD"], &["convert", "1", "USD", "PLN", "--date", "yesterday"], &["teleport"]] {
+            let output = task("usage", &server.credentials(), args).await;
+            assert_eq!(output.status.code(), Some(2), "{:?}", args);
+            assert!(output.stdout.is_empty());
+      }
+      let unknown = task("unknown", &server.credentials(), &["convert", "1", "USD", "XD"]).await;
+      assert_eq!(unknown.status.code(), Some(2));
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_help_exits_with_0() {
+      let credentials = Credentials{ url: "http://127.0.0.1:1".to_string(), api_key: "key".to_string() };
+      let output = task("help", &credentials, &["--help"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      assert!(stdout(&output).contains("convert"));
+}
+
+#[tokio::test]
+async fn test_api_errors_exit_with_3() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let credentials = Credentials{ api_key: "wrong-key".to_string(), ..server.credentials() };
+      let output = task("api", &credentials, &["rates"]).await;
+      assert_eq!(output.status.code(), Some(3));
+      assert!(output.stdout.is_empty());
+}
+
+#[tokio::test]
+async fn test_network_errors_exit_with_4() {
+      let credentials = Credentials{ url: "http://127.0.0.1:1".to_string(), api_key: "key".to_string() };
+      assert_eq!(task("network-convert", &credentials, &["convert", "1", "USD", "PLN"]).await.status.code(), Some(4));
+      assert_eq!(task("network-health", &credentials, &["health"]).await.status.code(), Some(4));
+}
+
+#[tokio::test]
+async fn test_json_output_has_a_stable_schema() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("json-convert", &server.credentials(), &["--output", "json", "convert", "100", "USD", "PLN"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      let conversion: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+      assert_eq!(conversion, serde_json::json!({
+            "from": "USD", "to": "PLN", "amount": "100", "result": "395.12", "rate": "3.9512", "date": "2024-03-09", "provider": "currencybeacon",
+      }));
+
+      let output = task("json-rates", &server.credentials(), &["rates", "--symbols", "PLN,EUR", "-o", "json"]).await;
+      let rates: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+      assert_eq!(rates, serde_json::json!({
+            "base": "USD", "date": "2024-03-09", "provider": "currencybeacon",
+            "rates": [{ "code": "EUR", "rate": "0.9146" }, { "code": "PLN", "rate": "3.9512" }],
+      }));
+
+      let output = task("json-currencies", &server.credentials(), &["currencies", "-o", "json"]).await;
+      let currencies: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+      assert_eq!(currencies["currencies"][0], serde_json::json!({ "code": "CAD", "name": "Canadian Dollar" }));
+
+      let output = task("json-health", &server.credentials(), &["health", "-o", "json"]).await;
+      let health: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+      assert_eq!(health["reachable"], true);
+      assert_eq!(health["status"], 200);
+}
+
+#[tokio::test]
+async fn test_csv_output_has_a_header_and_quotes_names() {
+      let fixtures = Fixtures::from_json(&include_str!("../fixtures/currencybeacon.json").replace("\"Euro\"", "\"Euro, \\\"the\\\" common currency\""));
+      let server = MockServer::start(MockConfig{ fixtures: fixtures.unwrap(), ..MockConfig::default() }).await.unwrap();
+      let output = task("csv-currencies", &server.credentials(), &["currencies", "--output", "csv"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      let lines: Vec<String> = stdout(&output).lines().map(str::to_string).collect();
+      assert_eq!(lines[0], "code,name");
+      assert!(lines.contains(&"EUR,\"Euro, \"\"the\"\" common currency\"".to_string()), "{:?}", lines);
+
+      let output = task("csv-rates", &server.credentials(), &["rates", "--symbols", "PLN", "--output", "csv"]).await;
+      assert_eq!(stdout(&output), "base,date,code,rate\nUSD,2024-03-09,PLN,3.9512\n");
+}
+
+#[tokio::test]
+async fn test_table_output_is_aligned_and_sorted() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("table", &server.credentials(), &["rates", "--symbols", "JPY,EUR,PLN", "--output", "table", "--sort", "rate", "--desc"]).await;
+      assert_eq!(output.status.code(), Some(0));
+      assert_eq!(stdout(&output), concat!(
+            "BASE  DATE        CODE    RATE\n",
+            "----  ----------  ----  ------\n",
+            "USD   2024-03-09  JPY   147.06\n",
+            "USD   2024-03-09  PLN   3.9512\n",
+            "USD   2024-03-09  EUR   0.9146\n",
+      ));
+
+      let output = task("table-names", &server.credentials(), &["currencies", "-o", "plain", "--sort", "name"]).await;
+      let codes: Vec<String> = stdout(&output).lines().map(|line| line[..3].to_string()).collect();
+      assert_eq!(codes.first().map(String::as_str), Some("GBP"));
+}
+
+#[tokio::test]
+async fn test_menu_prints_prompts_and_results_to_stdout() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = interact("menu", &server.credentials(), &[], "3\nUSD\n4\n100\nUSD\nPLN\n\n\nexit\n").await;
+      assert_eq!(output.status.code(), Some(0));
+      let out = stdout(&output);
+      assert!(out.contains("Type '4' => to exchange currencies"));
+      assert!(out.contains("Choose param 'base': "));
+      assert!(out.contains("\nPLN:3.9512\n"));
+      assert!(out.contains("395.12\nSuccess!"));
+      assert_eq!(stderr(&output), "");
+}
+
+#[tokio::test]
+async fn test_menu_ends_with_its_input() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = interact("menu-eof", &server.credentials(), &[], "3\n").await;
+      assert_eq!(output.status.code(), Some(0));
+      let paths: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+      assert_eq!(paths, vec!["/v1/currencies".to_string()]);
+}
+
+#[tokio::test]
+async fn test_menu_checks_currencies_before_asking_the_api() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = interact("menu-typos", &server.credentials(), &[], "4\n1\nusd\nxd\n3\nxd\n5\nUSD\n2024-03-05\nPLN,EUX\n3\neur\n").await;
+      assert_eq!(output.status.code(), Some(0));
+      assert_eq!(stderr(&output), concat!(
+            "error: unknown currency: XD\n",
+            "error: You picked up wrong base currency, such does not exist\n",
+            "error: unknown currency: EUX\n",
+      ));
+      let paths: Vec<String> = server.requests().into_iter().map(|request| request.path).collect();
+      assert_eq!(paths, vec!["/v1/currencies".to_string(), "/v1/latest".to_string()]);
+}
+
+#[tokio::test]
+async fn test_menu_shows_errors_on_stderr() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = interact("menu-errors", &server.credentials(), &[], "4\nlots\n5\nUSD\nyesterday\n").await;
+      assert_eq!(output.status.code(), Some(0));
+      assert_eq!(stderr(&output), "error: Amount must be number\nerror: Date must be in YYYY-MM-DD format\n");
+}
+
+#[tokio::test]
+async fn test_verbosity_flags_control_logging() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let quiet = task("log-default", &server.credentials(), &["health"]).await;
+      assert_eq!(stderr(&quiet), "");
+
+      let verbose = task("log-verbose", &server.credentials(), &["health", "-v"]).await;
+      assert!(stderr(&verbose).contains("INFO"), "{}", stderr(&verbose));
+      assert!(!stderr(&verbose).contains("DEBUG"));
+      assert_eq!(stdout(&verbose), "200\n");
+
+      let debug = task("log-debug", &server.credentials(), &["-vv", "health"]).await;
+      assert!(stderr(&debug).contains("DEBUG"));
+
+      let conflicting = task("log-conflict", &server.credentials(), &["-v", "-q", "health"]).await;
+      assert_eq!(conflicting.status.code(), Some(2));
+}
+
+#[tokio::test]
+async fn test_errors_go_to_stderr_without_log_prefixes() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("stderr", &server.credentials(), &["-q", "convert", "1", "USD", "XD"]).await;
+      assert_eq!(output.status.code(), Some(2));
+      assert!(output.stdout.is_empty());
+      assert_eq!(stderr(&output), "error: unknown currency: USD or XD\n");
+}
+
+#[tokio::test]
+async fn test_tui_needs_a_terminal() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let output = task("tui", &server.credentials(), &["tui"]).await;
+      assert_eq!(output.status.code(), Some(1));
+      assert!(output.stdout.is_empty());
+      assert_eq!(stderr(&output), "error: the terminal UI needs a terminal, use the subcommands from scripts\n");
+      assert!(server.requests().is_empty());
+}
@@ -0,0 +1,108 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use chrono::NaiveDate;
+use reqwest::StatusCode;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use task::api::cache::CacheInfo;
+use task::api::money::Money;
+use task::api::provider::{Conversion, Currencies, CurrencyInfo, ProviderFuture, RateProvider, Rates};
+use task::api::ApiError;
+
+/// A provider quoting fixed rates against a single base, dated 2024-03-09 unless asked for a date.
+/// Other bases are unknown currencies, and every call fails while `down` is set. `calls` counts
+/// the calls, both flags can be cloned out before the stub is boxed.
+pub struct StubProvider{
+      pub name: &'static str,
+      pub base: &'static str,
+      pub rates: BTreeMap<String, Decimal>,
+      pub down: Arc<AtomicBool>,
+      pub calls: Arc<AtomicU32>,
+}
+
+impl StubProvider {
+  /// A provider named `name` quoting 4 PLN and 0.8 EUR for a USD.
+  pub fn new(name: &'static str) -> Self {
+      StubProvider{
+            name,
+            base: "USD",
+            rates: BTreeMap::from([("PLN".to_string(), dec!(4)), ("EUR".to_string(), dec!(0.8))]),
+            down: Arc::new(AtomicBool::new(false)),
+            calls: Arc::new(AtomicU32::new(0)),
+      }
+  }
+
+  /// The same provider quoting `rates` instead.
+  pub fn quoting<'a>(mut self, rates: impl IntoIterator<Item = (&'a str, Decimal)>) -> Self {
+      self.rates = rates.into_iter().map(|(code, rate)| (code.to_string(), rate)).collect();
+      self
+  }
+
+  /// The same provider quoting its rates against `base`.
+  pub fn based_on(mut self, base: &'static str) -> Self {
+      self.base = base;
+      self
+  }
+
+  /// The same provider, down until `down` is cleared.
+  pub fn failing(self) -> Self {
+      self.down.store(true, Ordering::SeqCst);
+      self
+  }
+
+  fn answer(&self) -> Result<(), ApiError> {
+      self.calls.fetch_add(1, Ordering::SeqCst);
+      if self.down.load(Ordering::SeqCst) {
+            return Err(ApiError::Status{ status: StatusCode::SERVICE_UNAVAILABLE, detail: None, source: None });
+      }
+      Ok(())
+  }
+
+  fn rates(&self, base: &str, date: NaiveDate) -> Result<Rates, ApiError> {
+      self.answer()?;
+      if !base.eq_ignore_ascii_case(self.base) {
+            return Err(ApiError::UnknownCurrency{ codes: vec![base.to_ascii_uppercase()] });
+      }
+      Ok(Rates{ provider: self.name.to_string(), base: self.base.to_string(), date, rates: self.rates.clone(), cache: CacheInfo::default() })
+  }
+}
+
+fn today() -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+}
+
+impl RateProvider for StubProvider {
+  fn name(&self) -> &str {
+      self.name
+  }
+
+  fn latest(&self, args: RatiosListArguments) -> ProviderFuture<'_, Rates> {
+      Box::pin(async move { self.rates(&args.base, today()) })
+  }
+
+  fn historical(&self, args: HistoricalRatesArguments) -> ProviderFuture<'_, Rates> {
+      Box::pin(async move { self.rates(&args.base, args.date) })
+  }
+
+  fn convert(&self, args: ExchangeCurrencyArguments) -> ProviderFuture<'_, Conversion> {
+      Box::pin(async move {
+            let latest = self.rates(&args.from, args.date.unwrap_or_else(today))?;
+            let rate = *latest.rates.get(&args.to).ok_or_else(|| ApiError::UnknownCurrency{ codes: vec![args.to.clone()] })?;
+            let from = Money::new(args.amount, &args.from);
+            Ok(Conversion{ provider: self.name.to_string(), to: from.checked_convert(rate, &args.to)?.round(args.rounding), from, rate: Some(rate), date: latest.date, cache: CacheInfo::default() })
+      })
+  }
+
+  fn currencies(&self) -> ProviderFuture<'_, Currencies> {
+      Box::pin(async move {
+            self.answer()?;
+            let currencies = self.rates.keys().map(|code| CurrencyInfo{ code: code.clone(), name: None }).collect();
+            Ok(Currencies{ provider: self.name.to_string(), currencies, cache: CacheInfo::default() })
+      })
+  }
+}
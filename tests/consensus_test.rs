@@ -0,0 +1,119 @@
+mod common;
+
+use common::StubProvider;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, RatiosListArguments};
+use task::api::consensus::{Aggregation, ConsensusConfig, ConsensusProvider};
+use task::api::money::{Money, RoundingPolicy};
+use task::api::provider::RateProvider;
+use task::api::ApiError;
+
+fn quoting(name: &'static str, pln: Decimal, eur: Decimal) -> Box<dyn RateProvider> {
+      Box::new(StubProvider::new(name).quoting([("PLN", pln), ("EUR", eur)]))
+}
+
+fn failing(name: &'static str) -> Box<dyn RateProvider> {
+      Box::new(StubProvider::new(name).failing())
+}
+
+fn usd() -> RatiosListArguments {
+      RatiosListArguments{ base: "usd".to_string() }
+}
+
+#[tokio::test]
+async fn test_consensus_median_flags_outlier() {
+      let consensus = ConsensusProvider::new(vec![
+            quoting("a", dec!(4.00), dec!(0.92)),
+            quoting("b", dec!(4.02), dec!(0.92)),
+            quoting("c", dec!(4.50), dec!(0.92)),
+      ]);
+      let result = consensus.latest_consensus(usd()).await.unwrap();
+      assert_eq!(result.base, "USD");
+      assert_eq!(result.aggregation, Aggregation::Median);
+      let pln = &result.rates["PLN"];
+      assert_eq!(pln.rate, dec!(4.02));
+      assert_eq!(pln.sources.len(), 3);
+      let outlier = pln.sources.iter().find(|quote| quote.provider == "c").unwrap();
+      assert_eq!(outlier.rate, dec!(4.50));
+      assert_eq!(outlier.deviation, dec!(11.9403));
+      assert!(outlier.flagged);
+      assert!(!pln.sources.iter().find(|quote| quote.provider == "a").unwrap().flagged);
+      assert_eq!(result.flagged().into_iter().collect::<Vec<_>>(), vec!["c"]);
+}
+
+#[tokio::test]
+async fn test_consensus_mean_with_custom_threshold() {
+      let consensus = ConsensusProvider::new(vec![quoting("a", dec!(4.00), dec!(0.90)), quoting("b", dec!(4.20), dec!(0.94))])
+            .with_config(ConsensusConfig{ aggregation: Aggregation::Mean, max_deviation: dec!(5) });
+      let result = consensus.latest_consensus(usd()).await.unwrap();
+      assert_eq!(result.rates["PLN"].rate, dec!(4.10));
+      assert_eq!(result.rates["EUR"].rate, dec!(0.92));
+      assert!(result.flagged().is_empty());
+}
+
+#[tokio::test]
+async fn test_consensus_median_of_even_count_is_mean_of_middle_quotes() {
+      let consensus = ConsensusProvider::new(vec![
+            quoting("a", dec!(4.0), dec!(0.9)),
+            quoting("b", dec!(4.1), dec!(0.9)),
+            quoting("c", dec!(4.3), dec!(0.9)),
+            quoting("d", dec!(9.9), dec!(0.9)),
+      ]);
+      let result = consensus.latest_consensus(usd()).await.unwrap();
+      assert_eq!(result.rates["PLN"].rate, dec!(4.2));
+}
+
+#[tokio::test]
+async fn test_consensus_lists_failed_sources() {
+      let consensus = ConsensusProvider::new(vec![quoting("a", dec!(4), dec!(0.9)), failing("down")]);
+      let result = consensus.latest_consensus(usd()).await.unwrap();
+      assert_eq!(result.failures.len(), 1);
+      assert_eq!(result.failures[0].provider, "down");
+      assert_eq!(result.rates["PLN"].rate, dec!(4));
+
+      let nobody = ConsensusProvider::new(vec![failing("down"), failing("also down")]);
+      assert!(matches!(nobody.latest_consensus(usd()).await.unwrap_err(), ApiError::Status{ .. }));
+}
+
+#[tokio::test]
+async fn test_consensus_as_rate_provider() {
+      let consensus = ConsensusProvider::new(vec![quoting("a", dec!(4.00), dec!(0.92)), quoting("b", dec!(4.02), dec!(0.92)), quoting("c", dec!(4.50), dec!(0.92))]);
+      let provider: &dyn RateProvider = &consensus;
+      let rates = provider.latest(usd()).await.unwrap();
+      assert_eq!(rates.provider, "consensus");
+      assert_eq!(rates.rates["PLN"], dec!(4.02));
+
+      let conversion = provider.convert(ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(100),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap();
+      assert_eq!(conversion.to, Money::new(dec!(402.00), "PLN"));
+
+      let currencies = provider.currencies().await.unwrap();
+      assert_eq!(currencies.currencies.len(), 2);
+}
+
+#[tokio::test]
+async fn test_consensus_convert_reports_amounts_too_large() {
+      let consensus = ConsensusProvider::new(vec![quoting("a", dec!(4.00), dec!(0.92)), quoting("b", dec!(4.02), dec!(0.92))]);
+      let err = consensus.convert(ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: Decimal::MAX,
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ ref reason, .. } if reason == "amount too large"), "{:?}", err);
+}
+
+#[test]
+fn test_aggregation_parses_names() {
+      assert_eq!("Median".parse::<Aggregation>(), Ok(Aggregation::Median));
+      assert_eq!("average".parse::<Aggregation>(), Ok(Aggregation::Mean));
+      assert!("mode".parse::<Aggregation>().is_err());
+      assert_eq!(Aggregation::Mean.to_string(), "mean");
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::PathBuf;
+use task::api::credentials::{Credentials, CredentialsError};
+
+fn config_file(name: &str, contents: &str) -> PathBuf {
+      let path = std::env::temp_dir().join(format!("task-credentials-{}-{}.toml", name, std::process::id()));
+      fs::write(&path, contents).unwrap();
+      path
+}
+
+#[test]
+fn test_credentials_explicit_values() {
+      let credentials = Credentials::builder()
+            .url("https://api.currencybeacon.com")
+            .api_key("secret")
+            .ignore_env()
+            .ignore_default_config_file()
+            .build()
+            .unwrap();
+      assert_eq!(credentials.url, "https://api.currencybeacon.com");
+      assert_eq!(credentials.api_key, "secret");
+      assert!(!format!("{:?}", credentials).contains("secret"));
+}
+
+#[test]
+fn test_credentials_missing_setting_is_named() {
+      let err = Credentials::builder()
+            .url("https://api.currencybeacon.com")
+            .ignore_env()
+            .ignore_default_config_file()
+            .build()
+            .unwrap_err();
+      assert!(matches!(err, CredentialsError::Missing { setting: "API_KEY", config_file: None }));
+      assert!(err.to_string().starts_with("API_KEY is not defined"));
+}
+
+#[test]
+fn test_credentials_from_config_file_with_explicit_precedence() {
+      let path = config_file("precedence", "api_url = \"https://file.example\"\napi_key = \"from-file\"\n");
+      let credentials = Credentials::builder()
+            .api_key("explicit")
+            .config_file(&path)
+            .ignore_env()
+            .build()
+            .unwrap();
+      assert_eq!(credentials.url, "https://file.example");
+      assert_eq!(credentials.api_key, "explicit");
+      fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_credentials_config_file_missing_key_names_the_file() {
+      let path = config_file("missing", "api_url = \"https://file.example\"\n");
+      let err = Credentials::builder().config_file(&path).ignore_env().build().unwrap_err();
+      assert!(matches!(&err, CredentialsError::Missing { setting: "API_KEY", config_file: Some(file) } if file == &path));
+      assert!(err.to_string().contains("`api_key`"));
+      fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_credentials_invalid_config_file() {
+      let path = config_file("invalid", "api_url = [1, 2\n");
+      let err = Credentials::builder().config_file(&path).ignore_env().build().unwrap_err();
+      assert!(matches!(err, CredentialsError::ConfigFileInvalid { .. }));
+      fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_credentials_explicit_config_file_must_exist() {
+      let path = std::env::temp_dir().join("task-credentials-does-not-exist.toml");
+      let err = Credentials::builder().config_file(&path).ignore_env().build().unwrap_err();
+      assert!(matches!(err, CredentialsError::ConfigFileUnreadable { .. }));
+}
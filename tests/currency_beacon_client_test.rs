@@ -0,0 +1,29 @@
+use std::time::Duration;
+use task::api::credentials::Credentials;
+use task::api::{ApiError, ClientConfig, CurrencyBeaconClient};
+
+#[test]
+fn test_client_trims_trailing_slash_of_base_url() {
+      let credentials = Credentials{ url: "https://api.currencybeacon.com/".to_string(), api_key: "key".to_string() };
+      let client = CurrencyBeaconClient::new(&credentials).unwrap();
+      assert_eq!(client.base_url(), "https://api.currencybeacon.com");
+}
+
+#[test]
+fn test_client_rejects_invalid_base_url() {
+      let credentials = Credentials{ url: "not a url".to_string(), api_key: "key".to_string() };
+      let err = CurrencyBeaconClient::new(&credentials).unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments { source: Some(_), .. }));
+}
+
+#[test]
+fn test_client_accepts_custom_config() {
+      let credentials = Credentials{ url: "https://api.currencybeacon.com".to_string(), api_key: "key".to_string() };
+      let config = ClientConfig{
+            connect_timeout: Duration::from_millis(250),
+            read_timeout: Duration::from_secs(1),
+            user_agent: "reporting-service/2.0".to_string(),
+            ..ClientConfig::default()
+      };
+      assert!(CurrencyBeaconClient::with_config(&credentials, config).is_ok());
+}
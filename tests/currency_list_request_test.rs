@@ -1,4 +1,3 @@
-pub(crate) use core::any::Any;
 use serde_json::{json, Value};
 use task::api::Requests;
 use task::api::Result;
@@ -6,14 +5,16 @@ pub struct CurrencyListRequestsMock {}
 /// Mocking CurrencyListRequest
 ///testing functionality by faking data instead of taking it from external API call
 impl Requests for CurrencyListRequestsMock {
-    async fn call(&mut self) -> Result<Box<dyn Any>> {
+    type Output = Value;
+
+    async fn call(&mut self) -> Result<Value> {
         let json =  json!({
               "USD": "United States dollar",
               "PLN": "Polish Zloty",
               "AUD": "Australian dollar",
               "EUR": "Euro",
             });
-        Ok(Box::new(json))
+        Ok(json)
     }
 }
 
@@ -21,11 +22,11 @@ impl Requests for CurrencyListRequestsMock {
 #[tokio::test]
 async fn test_currency_list_request_succesful() {
       let mut client = CurrencyListRequestsMock{};
-      let result: Result<Box<dyn Any>> = client.call().await;
-      let value: Value = *result.unwrap().downcast::<Value>().unwrap();
+      let result: Result<Value> = client.call().await;
+      let value: Value = result.unwrap();
       assert_eq!(value.as_object().unwrap().len(), 4);
       assert_eq!(value["USD"].to_string(), "\"United States dollar\"");
       assert_eq!(value["PLN"].to_string(), "\"Polish Zloty\"");
       assert_eq!(value["AUD"].to_string(), "\"Australian dollar\"");
       assert_eq!(value["EUR"].to_string(), "\"Euro\"");
-}
\ No newline at end of file
+}
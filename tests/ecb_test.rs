@@ -0,0 +1,142 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use task::api::ecb::{EcbError, EcbProvider};
+use task::api::money::{Money, RoundingPolicy};
+use task::api::{ApiError, RateProvider};
+
+const DAILY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+	<gesmes:subject>Reference rates</gesmes:subject>
+	<gesmes:Sender>
+		<gesmes:name>European Central Bank</gesmes:name>
+	</gesmes:Sender>
+	<Cube>
+		<Cube time='2024-03-08'>
+			<Cube currency='USD' rate='1.0932'/>
+			<Cube currency='JPY' rate='160.68'/>
+			<Cube currency='PLN' rate='4.3090'/>
+		</Cube>
+	</Cube>
+</gesmes:Envelope>"#;
+
+const HISTORICAL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01" xmlns="http://www.ecb.int/vocabulary/2002-08-01/eurofxref">
+	<gesmes:subject>Reference rates</gesmes:subject>
+	<Cube>
+		<Cube time="2024-03-07">
+			<Cube currency="USD" rate="1.0895"/>
+			<Cube currency="PLN" rate="4.3145"/>
+		</Cube>
+		<Cube time="2024-03-01">
+			<Cube currency="USD" rate="1.0830"/>
+			<Cube currency="PLN" rate="4.3208"/>
+		</Cube>
+	</Cube>
+</gesmes:Envelope>"#;
+
+fn date(day: u32) -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+}
+
+#[tokio::test]
+async fn test_ecb_latest_is_quoted_against_euro() {
+      let provider = EcbProvider::from_xml(DAILY).unwrap();
+      let rates = provider.latest(RatiosListArguments{ base: "eur".to_string() }).await.unwrap();
+      assert_eq!(rates.provider, "ecb");
+      assert_eq!(rates.base, "EUR");
+      assert_eq!(rates.date, date(8));
+      assert_eq!(rates.rates["USD"], dec!(1.0932));
+      assert!(!rates.rates.contains_key("EUR"));
+}
+
+#[tokio::test]
+async fn test_ecb_latest_crosses_through_euro() {
+      let provider = EcbProvider::from_xml(DAILY).unwrap();
+      let rates = provider.latest(RatiosListArguments{ base: "USD".to_string() }).await.unwrap();
+      assert_eq!(rates.rates["EUR"], dec!(1) / dec!(1.0932));
+      assert_eq!(rates.rates["PLN"], dec!(4.3090) / dec!(1.0932));
+      assert!(!rates.rates.contains_key("USD"));
+}
+
+#[tokio::test]
+async fn test_ecb_historical_falls_back_to_last_published_day() {
+      let provider = EcbProvider::from_xml(HISTORICAL).unwrap();
+      // 2024-03-03 was a Sunday, the last rates before it are of Friday 2024-03-01.
+      let rates = provider.historical(HistoricalRatesArguments{ date: date(3), base: "EUR".to_string(), symbols: Some(vec!["pln".to_string()]) }).await.unwrap();
+      assert_eq!(rates.date, date(1));
+      assert_eq!(rates.rates.len(), 1);
+      assert_eq!(rates.rates["PLN"], dec!(4.3208));
+
+      let err = provider.historical(HistoricalRatesArguments{ date: NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), base: "EUR".to_string(), symbols: None }).await.unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ .. }));
+}
+
+#[tokio::test]
+async fn test_ecb_convert_crosses_and_rounds() {
+      let provider = EcbProvider::from_xml(HISTORICAL).unwrap();
+      let conversion = provider.convert(ExchangeCurrencyArguments{
+            from: "usd".to_string(),
+            to: "pln".to_string(),
+            amount: dec!(100),
+            date: Some(date(7)),
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap();
+      assert_eq!(conversion.date, date(7));
+      assert_eq!(conversion.rate, Some(dec!(4.3145) / dec!(1.0895)));
+      assert_eq!(conversion.to, Money::new(dec!(396.01), "PLN"));
+}
+
+#[tokio::test]
+async fn test_ecb_convert_reports_amounts_too_large() {
+      let provider = EcbProvider::from_xml(DAILY).unwrap();
+      let err = provider.convert(ExchangeCurrencyArguments{
+            from: "EUR".to_string(),
+            to: "JPY".to_string(),
+            amount: Decimal::MAX,
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ ref reason, .. } if reason == "amount too large"), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_ecb_reports_unknown_currency() {
+      let provider = EcbProvider::from_xml(DAILY).unwrap();
+      let err = provider.convert(ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "XD".to_string(),
+            amount: dec!(1),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ codes } if codes == vec!["XD".to_string()]));
+      let err = provider.latest(RatiosListArguments{ base: "XD".to_string() }).await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ .. }));
+}
+
+#[tokio::test]
+async fn test_ecb_reads_directory_of_files() {
+      let dir = std::env::temp_dir().join(format!("task-ecb-test-{}", std::process::id()));
+      let _ = std::fs::remove_dir_all(&dir);
+      std::fs::create_dir_all(&dir).unwrap();
+      std::fs::write(dir.join("eurofxref-daily.xml"), DAILY).unwrap();
+      std::fs::write(dir.join("eurofxref-hist.xml"), HISTORICAL).unwrap();
+      std::fs::write(dir.join("README.txt"), "not rates").unwrap();
+
+      let provider = EcbProvider::open(&dir).unwrap();
+      assert_eq!(provider.range(), (date(1), date(8)));
+      let currencies = provider.currencies().await.unwrap();
+      let codes: Vec<&str> = currencies.currencies.iter().map(|currency| currency.code.as_str()).collect();
+      assert_eq!(codes, vec!["EUR", "JPY", "PLN", "USD"]);
+      let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_ecb_rejects_invalid_documents() {
+      assert!(matches!(EcbProvider::from_xml("<Cube>"), Err(EcbError::InvalidXml{ .. })));
+      assert!(matches!(EcbProvider::from_xml("<Cube><Cube time='2024-03-08'><Cube currency='USD' rate='abc'/></Cube></Cube>"), Err(EcbError::InvalidCube{ .. })));
+      assert!(matches!(EcbProvider::from_xml("<Cube/>"), Err(EcbError::NoRates{ .. })));
+      assert!(matches!(EcbProvider::open("/does/not/exist.xml"), Err(EcbError::Unreadable{ .. })));
+}
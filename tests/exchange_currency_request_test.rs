@@ -1,7 +1,9 @@
-pub(crate) use core::any::Any;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde_json::json;
 use task::api::arguments::ExchangeCurrencyArguments;
-use task::api::Requests;
+use task::api::money::RoundingPolicy;
+use task::api::{ApiError, Requests};
 use task::api::Result;
 pub struct ExchangeCurrencyRequestsMock {
   pub args: ExchangeCurrencyArguments,
@@ -9,7 +11,9 @@ pub struct ExchangeCurrencyRequestsMock {
 /// Mocking ExchangeCurrencyRequest
 /// testing functionality by faking data instead of taking it from external API call
 impl Requests for ExchangeCurrencyRequestsMock {
-    async fn call(&mut self) -> Result<Box<dyn Any>> {
+    type Output = Decimal;
+
+    async fn call(&mut self) -> Result<Decimal> {
         let json =  json!({
               "USD": {
                 "USD": 1.0,
@@ -25,11 +29,11 @@ impl Requests for ExchangeCurrencyRequestsMock {
               }
             });
         if json[&self.args.from.to_ascii_uppercase()].is_null() || json[&self.args.from.to_ascii_uppercase()][&self.args.to.to_ascii_uppercase()].is_null(){
-          return Err("Request got bad".into())
+          return Err(ApiError::UnknownCurrency { codes: vec![self.args.from.to_ascii_uppercase(), self.args.to.to_ascii_uppercase()] })
         }
-        let rate: f64 = json[&self.args.from.to_ascii_uppercase()][&self.args.to.to_ascii_uppercase()].to_string().parse::<f64>().unwrap();
-        let value: f64 = self.args.amount* (rate * 100.00).round() / 100.00;
-        Ok(Box::new(value))
+        let rate: Decimal = json[&self.args.from.to_ascii_uppercase()][&self.args.to.to_ascii_uppercase()].to_string().parse::<Decimal>().unwrap();
+        let value: Decimal = self.args.amount* (rate * dec!(100.00)).round() / dec!(100.00);
+        Ok(value)
     }
 }
 
@@ -40,12 +44,14 @@ async fn test_exchange_currency_request() {
         args: ExchangeCurrencyArguments{
           from: "USD".to_string(),
           to: "AUD".to_string(),
-          amount: 121.6,
+          amount: dec!(121.6),
+          date: None,
+          rounding: RoundingPolicy::default(),
         }
       };
-      let result: Result<Box<dyn Any>> = client.call().await;
-      let value: f64 = *result.unwrap().downcast::<f64>().unwrap();
-      assert_eq!(value, 184.832);
+      let result: Result<Decimal> = client.call().await;
+      let value: Decimal = result.unwrap();
+      assert_eq!(value, dec!(184.832));
 }
 
 #[tokio::test]
@@ -54,10 +60,14 @@ async fn test_exchange_currency_request_non_existant_currency_to_returns_err() {
     args: ExchangeCurrencyArguments{
       from: "USD".to_string(),
       to: "xd".to_string(),
-      amount: 123.6,
+      amount: dec!(123.6),
+      date: None,
+      rounding: RoundingPolicy::default(),
     }
   };
-  assert_eq!(*client.call().await.unwrap_err().to_string(), "Request got bad".to_string());
+  let err = client.call().await.unwrap_err();
+  assert!(matches!(&err, ApiError::UnknownCurrency { codes } if codes == &vec!["USD".to_string(), "XD".to_string()]));
+  assert_eq!(err.to_string(), "unknown currency: USD or XD".to_string());
 }
 
 #[tokio::test]
@@ -66,8 +76,12 @@ async fn test_exchange_currency_request_non_existant_currency_from_returns_err()
     args: ExchangeCurrencyArguments{
       from: "xd".to_string(),
       to: "AUD".to_string(),
-      amount: 123.6,
+      amount: dec!(123.6),
+      date: None,
+      rounding: RoundingPolicy::default(),
     }
   };
-  assert_eq!(*client.call().await.unwrap_err().to_string(), "Request got bad".to_string());
+  let err = client.call().await.unwrap_err();
+  assert!(matches!(&err, ApiError::UnknownCurrency { codes } if codes == &vec!["XD".to_string(), "AUD".to_string()]));
+  assert_eq!(err.to_string(), "unknown currency: XD or AUD".to_string());
 }
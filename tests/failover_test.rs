@@ -0,0 +1,117 @@
+mod common;
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::NaiveDate;
+use common::StubProvider;
+use reqwest::StatusCode;
+use rust_decimal_macros::dec;
+use task::api::arguments::{HistoricalRatesArguments, RatiosListArguments};
+use task::api::failover::{FailoverProvider, HealthPolicy};
+use task::api::provider::RateProvider;
+use task::api::snapshot::{RecordingProvider, SnapshotProvider};
+use task::api::ApiError;
+
+fn latest() -> RatiosListArguments {
+      RatiosListArguments{ base: "USD".to_string() }
+}
+
+#[tokio::test]
+async fn test_failover_reports_the_provider_that_answered() {
+      let chain = FailoverProvider::new(vec![Box::new(StubProvider::new("primary").failing()), Box::new(StubProvider::new("secondary"))]);
+      let rates = chain.latest(latest()).await.unwrap();
+      assert_eq!(rates.provider, "secondary");
+      assert_eq!(chain.name(), "failover");
+}
+
+#[tokio::test]
+async fn test_failover_marks_provider_unhealthy_and_probes_it_later() {
+      let primary = StubProvider::new("primary").failing();
+      let (down, calls) = (primary.down.clone(), primary.calls.clone());
+      let chain = FailoverProvider::new(vec![Box::new(primary), Box::new(StubProvider::new("secondary"))])
+            .with_policy(HealthPolicy{ failure_threshold: 2, probe_after: Duration::from_millis(100) });
+
+      chain.latest(latest()).await.unwrap();
+      assert!(chain.health()[0].healthy);
+      chain.latest(latest()).await.unwrap();
+      assert!(!chain.health()[0].healthy);
+      assert_eq!(chain.health()[0].consecutive_failures, 2);
+
+      // Unhealthy and not due for a probe: the secondary answers without the primary being asked.
+      assert_eq!(chain.latest(latest()).await.unwrap().provider, "secondary");
+      assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+      down.store(false, Ordering::SeqCst);
+      tokio::time::sleep(Duration::from_millis(120)).await;
+      assert_eq!(chain.latest(latest()).await.unwrap().provider, "primary");
+      assert_eq!(calls.load(Ordering::SeqCst), 3);
+      assert!(chain.health()[0].healthy);
+      assert_eq!(chain.health()[0].consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn test_failover_falls_back_to_unhealthy_providers_as_last_resort() {
+      let primary = StubProvider::new("only").failing();
+      let down = primary.down.clone();
+      let lonely = FailoverProvider::new(vec![Box::new(primary)])
+            .with_policy(HealthPolicy{ failure_threshold: 1, probe_after: Duration::from_secs(60) });
+      assert!(lonely.latest(latest()).await.is_err());
+      assert!(!lonely.health()[0].healthy);
+      down.store(false, Ordering::SeqCst);
+      assert_eq!(lonely.latest(latest()).await.unwrap().provider, "only");
+      assert!(lonely.health()[0].healthy);
+}
+
+#[tokio::test]
+async fn test_failover_unknown_currency_does_not_hurt_health() {
+      let primary = StubProvider::new("primary").based_on("EUR");
+      let chain = FailoverProvider::new(vec![Box::new(primary), Box::new(StubProvider::new("secondary"))])
+            .with_policy(HealthPolicy{ failure_threshold: 1, probe_after: Duration::from_secs(60) });
+      assert_eq!(chain.latest(latest()).await.unwrap().provider, "secondary");
+      assert!(chain.health()[0].healthy);
+}
+
+#[tokio::test]
+async fn test_failover_returns_the_most_telling_error() {
+      let secondary = StubProvider::new("secondary").based_on("EUR");
+      let chain = FailoverProvider::new(vec![Box::new(StubProvider::new("primary").failing()), Box::new(secondary)]);
+      let err = chain.latest(latest()).await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ .. }));
+
+      let empty = FailoverProvider::new(vec![]);
+      assert!(matches!(empty.latest(latest()).await.unwrap_err(), ApiError::InvalidArguments{ .. }));
+}
+
+#[tokio::test]
+async fn test_failover_empty_snapshot_does_not_hide_the_cause() {
+      let chain = FailoverProvider::new(vec![Box::new(StubProvider::new("primary")), Box::new(SnapshotProvider::in_memory())]);
+      let err = chain.latest(RatiosListArguments{ base: "XD".to_string() }).await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ ref codes } if codes == &["XD".to_string()]), "{:?}", err);
+
+      let chain = FailoverProvider::new(vec![Box::new(StubProvider::new("primary").failing()), Box::new(SnapshotProvider::in_memory())]);
+      let err = chain.latest(latest()).await.unwrap_err();
+      assert!(matches!(err, ApiError::Status{ status: StatusCode::SERVICE_UNAVAILABLE, .. }), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_failover_keeps_the_snapshot_behind_it_up_to_date() {
+      let snapshot = Arc::new(SnapshotProvider::in_memory());
+      let live = StubProvider::new("live");
+      let down = live.down.clone();
+      let chain = FailoverProvider::new(vec![Box::new(RecordingProvider::new(live, snapshot.clone())), Box::new(snapshot.clone())]);
+      let historical = |day: u32| HistoricalRatesArguments{ date: NaiveDate::from_ymd_opt(2024, 3, day).unwrap(), base: "USD".to_string(), symbols: None };
+
+      chain.historical(historical(8)).await.unwrap();
+      assert_eq!(snapshot.rates().unwrap().date, NaiveDate::from_ymd_opt(2024, 3, 8).unwrap());
+      chain.latest(latest()).await.unwrap();
+      let recorded = snapshot.rates().unwrap();
+      assert_eq!((recorded.provider.as_str(), recorded.date), ("live", NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()));
+      chain.historical(historical(1)).await.unwrap();
+      assert_eq!(snapshot.rates().unwrap().date, NaiveDate::from_ymd_opt(2024, 3, 9).unwrap(), "older rates do not replace the snapshot");
+
+      down.store(true, Ordering::SeqCst);
+      let rates = chain.latest(RatiosListArguments{ base: "PLN".to_string() }).await.unwrap();
+      assert_eq!(rates.provider, "snapshot");
+      assert_eq!(rates.rates["USD"], dec!(0.25));
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use task::api::arguments::RatiosListArguments;
+use task::api::retry::RetryPolicy;
+use task::api::{ApiError, ClientConfig, CurrencyBeaconClient, RatiosListRequest, Requests, Result};
+use task::mock::{Fault, FaultProfile, MockConfig, MockServer};
+
+fn retries(max_attempts: u32) -> RetryPolicy {
+      RetryPolicy{ max_attempts, base_delay: Duration::from_millis(5), max_delay: Duration::from_millis(50), jitter: 0.0, ..RetryPolicy::default() }
+}
+
+async fn start(faults: FaultProfile, retry: RetryPolicy) -> (MockServer, CurrencyBeaconClient) {
+      let server = MockServer::start(MockConfig{ faults, ..MockConfig::default() }).await.unwrap();
+      let config = ClientConfig{ retry, read_timeout: Duration::from_millis(500), ..ClientConfig::default() };
+      let client = CurrencyBeaconClient::with_config(&server.credentials(), config).unwrap();
+      (server, client)
+}
+
+async fn latest(client: &CurrencyBeaconClient) -> Result<()> {
+      (RatiosListRequest{ client, args: RatiosListArguments{ base: "USD".to_string() } }).call().await.map(|_| ())
+}
+
+#[test]
+fn test_fault_names_round_trip() {
+      for name in ["latency:250", "429", "429:3", "500", "503", "truncated", "html", "reset"] {
+            let fault: Fault = name.parse().unwrap();
+            assert_eq!(fault.to_string(), name);
+      }
+      assert_eq!("LATENCY:10".parse::<Fault>(), Ok(Fault::Latency(Duration::from_millis(10))));
+      assert!("latency".parse::<Fault>().is_err());
+      assert!("200".parse::<Fault>().is_err());
+      assert!("explode".parse::<Fault>().is_err());
+}
+
+#[tokio::test]
+async fn test_server_error_is_retried_until_success() {
+      let (server, client) = start(FaultProfile::none().first(2, Fault::Status(500)), retries(3)).await;
+      latest(&client).await.unwrap();
+      assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_server_error_maps_to_status_without_retries() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::Status(503)), RetryPolicy::none()).await;
+      match latest(&client).await {
+            Err(ApiError::Status{ status, detail, .. }) => {
+                  assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+                  assert!(detail.is_some());
+            }
+            other => panic!("expected a status error, got {:?}", other),
+      }
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_retries_give_up_after_max_attempts() {
+      let (server, client) = start(FaultProfile::none().first(5, Fault::Status(500)), retries(3)).await;
+      assert!(matches!(latest(&client).await, Err(ApiError::Status{ status: StatusCode::INTERNAL_SERVER_ERROR, .. })));
+      assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_rate_limit_maps_to_quota_exceeded_with_retry_after() {
+      let faults = FaultProfile::none().on_request(1, Fault::RateLimited{ retry_after: Some(Duration::from_secs(7)) });
+      let (_server, client) = start(faults, RetryPolicy::none()).await;
+      match latest(&client).await {
+            Err(ApiError::QuotaExceeded{ retry_after, .. }) => assert_eq!(retry_after, Some(Duration::from_secs(7))),
+            other => panic!("expected quota exceeded, got {:?}", other),
+      }
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_max_delay_is_not_waited_for() {
+      let faults = FaultProfile::none().on_request(1, Fault::RateLimited{ retry_after: Some(Duration::from_secs(60)) });
+      let (server, client) = start(faults, retries(3)).await;
+      assert!(matches!(latest(&client).await, Err(ApiError::QuotaExceeded{ .. })));
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_rate_limit_without_retry_after_is_retried() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::RateLimited{ retry_after: None }), retries(2)).await;
+      latest(&client).await.unwrap();
+      assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_latency_beyond_read_timeout_is_a_transport_timeout() {
+      let (_server, client) = start(FaultProfile::none().on_request(1, Fault::Latency(Duration::from_secs(3))), RetryPolicy::none()).await;
+      match latest(&client).await {
+            Err(ApiError::Transport(err)) => assert!(err.is_timeout(), "{:?}", err),
+            other => panic!("expected a timeout, got {:?}", other),
+      }
+}
+
+#[tokio::test]
+async fn test_latency_within_read_timeout_is_answered() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::Latency(Duration::from_millis(50))), RetryPolicy::none()).await;
+      latest(&client).await.unwrap();
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_timeout_is_retried() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::Latency(Duration::from_secs(3))), retries(2)).await;
+      latest(&client).await.unwrap();
+      assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_truncated_json_is_malformed_and_not_retried() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::TruncatedJson), retries(3)).await;
+      match latest(&client).await {
+            Err(ApiError::MalformedPayload{ body, source }) => {
+                  assert!(body.starts_with('{'));
+                  assert!(source.is_eof(), "{:?}", source);
+            }
+            other => panic!("expected a malformed payload, got {:?}", other),
+      }
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_wrong_content_type_is_malformed() {
+      let (_server, client) = start(FaultProfile::none().on_request(1, Fault::WrongContentType), retries(3)).await;
+      match latest(&client).await {
+            Err(ApiError::MalformedPayload{ body, .. }) => assert!(body.contains("<html>")),
+            other => panic!("expected a malformed payload, got {:?}", other),
+      }
+}
+
+#[tokio::test]
+async fn test_connection_reset_is_a_transport_error() {
+      let (_server, client) = start(FaultProfile::none().on_request(1, Fault::Reset), RetryPolicy::none()).await;
+      assert!(matches!(latest(&client).await, Err(ApiError::Transport(_))));
+}
+
+#[tokio::test]
+async fn test_connection_reset_is_retried() {
+      let (server, client) = start(FaultProfile::none().on_request(1, Fault::Reset), retries(2)).await;
+      latest(&client).await.unwrap();
+      assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_scripted_fault_wins_over_random_ones() {
+      let faults = FaultProfile::none().with_probability(1.0, Fault::Status(500)).on_request(2, Fault::Status(404));
+      let (_server, client) = start(faults, RetryPolicy::none()).await;
+      assert!(matches!(latest(&client).await, Err(ApiError::Status{ status: StatusCode::INTERNAL_SERVER_ERROR, .. })));
+      assert!(matches!(latest(&client).await, Err(ApiError::Status{ status: StatusCode::NOT_FOUND, .. })));
+      assert!(matches!(latest(&client).await, Err(ApiError::Status{ status: StatusCode::INTERNAL_SERVER_ERROR, .. })));
+}
+
+#[tokio::test]
+async fn test_zero_probability_never_fails() {
+      let (_server, client) = start(FaultProfile::none().with_probability(0.0, Fault::Reset), RetryPolicy::none()).await;
+      for _ in 0..10 {
+            latest(&client).await.unwrap();
+      }
+}
+
+#[tokio::test]
+async fn test_seeded_random_faults_repeat() {
+      async fn outcomes(seed: u64) -> Vec<bool> {
+            let faults = FaultProfile::none().with_probability(0.5, Fault::Status(500)).seeded(seed);
+            let (_server, client) = start(faults, RetryPolicy::none()).await;
+            let mut outcomes = Vec::new();
+            for _ in 0..20 {
+                  outcomes.push(latest(&client).await.is_ok());
+            }
+            outcomes
+      }
+      let first = outcomes(42).await;
+      assert_eq!(first, outcomes(42).await);
+      assert!(first.contains(&true));
+      assert!(first.contains(&false));
+}
@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+use task::api::arguments::HistoricalRatesArguments;
+use task::api::cache::CacheInfo;
+use task::api::models::{Envelope, HistoricalResponse};
+use task::api::{ApiError, Requests};
+use task::api::Result;
+pub struct HistoricalRatesRequestMock {
+  pub args: HistoricalRatesArguments,
+}
+/// Mocking HistoricalRatesRequest
+/// testing functionality by faking data instead of taking it from external API call
+impl Requests for HistoricalRatesRequestMock {
+    type Output = HistoricalResponse;
+
+    async fn call(&mut self) -> Result<HistoricalResponse> {
+        let json =  json!({
+              "2024-01-02": {
+                "USD": { "USD": 1.0, "PLN": 3.96, "EUR": 0.91 },
+              },
+              "2024-01-03": {
+                "USD": { "USD": 1.0, "PLN": 3.99, "EUR": 0.92 },
+              }
+            });
+        let day: &Value = &json[self.args.date.format("%Y-%m-%d").to_string()][self.args.base.to_ascii_uppercase()];
+        if day.is_null() {
+          return Err(ApiError::UnknownCurrency { codes: vec![self.args.base.to_ascii_uppercase()] })
+        }
+        let rates = day.as_object().unwrap().iter()
+          .filter(|(code, _)| match &self.args.symbols {
+            Some(symbols) => symbols.contains(code),
+            None => true,
+          })
+          .map(|(code, rate)| (code.clone(), rate.to_string().parse().unwrap()))
+          .collect();
+        Ok(HistoricalResponse { date: self.args.date, base: self.args.base.to_ascii_uppercase(), rates, cache: CacheInfo::default() })
+    }
+}
+
+
+#[tokio::test]
+async fn test_historical_rates_request_succesful() {
+      let mut client = HistoricalRatesRequestMock{
+        args: HistoricalRatesArguments{
+          date: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+          base: "usd".to_string(),
+          symbols: Some(vec!["PLN".to_string()]),
+        }
+      };
+      let value: HistoricalResponse = client.call().await.unwrap();
+      assert_eq!(value.date, NaiveDate::from_ymd_opt(2024, 1, 3).unwrap());
+      assert_eq!(value.rates["PLN"], dec!(3.99));
+      assert_eq!(value.rates.len(), 1);
+}
+
+#[tokio::test]
+async fn test_historical_rates_request_non_existant_currency_base_returns_err() {
+  let mut client = HistoricalRatesRequestMock{
+    args: HistoricalRatesArguments{
+      date: NaiveDate::from_ymd_opt(2024, 1, 3).unwrap(),
+      base: "XD".to_string(),
+      symbols: None,
+    }
+  };
+  assert!(matches!(client.call().await.unwrap_err(), ApiError::UnknownCurrency { .. }));
+}
+
+#[test]
+fn test_historical_response_parses_effective_date() {
+      let envelope: Envelope<HistoricalResponse> = serde_json::from_value(json!({
+            "meta": { "code": 200 },
+            "response": { "date": "2024-01-02", "base": "USD", "rates": { "PLN": 3.96 } }
+      })).unwrap();
+      assert_eq!(envelope.response.date, NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+      assert_eq!(envelope.response.rates["PLN"], dec!(3.96));
+}
@@ -0,0 +1,228 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments, TimeSeriesArguments};
+use task::api::cache::CacheConfig;
+use task::api::credentials::Credentials;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::{
+      ApiError, ClientConfig, CurrencyBeaconClient, CurrencyListRequest, ExchangeCurrencyRequest, HistoricalRatesRequest,
+      RateProvider, RatiosListRequest, Request, Requests, TimeSeriesRequest,
+};
+use task::mock::{Fixtures, MockConfig, MockServer};
+
+fn date(day: u32) -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+}
+
+async fn start() -> (MockServer, CurrencyBeaconClient) {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let client = CurrencyBeaconClient::new(&server.credentials()).unwrap();
+      (server, client)
+}
+
+#[test]
+fn test_bundled_fixtures_are_valid() {
+      let fixtures = Fixtures::default();
+      assert_eq!(fixtures.base, "USD");
+      assert!(!fixtures.historical.is_empty());
+      assert!(fixtures.currencies.iter().any(|currency| currency.short_code == "PLN"));
+}
+
+#[tokio::test]
+async fn test_mock_serves_latest_rates() {
+      let (server, client) = &start().await;
+      let latest = (RatiosListRequest{ client, args: RatiosListArguments{ base: "usd".to_string() } }).call().await.unwrap();
+      assert_eq!(latest.base, "USD");
+      assert_eq!(latest.rates["PLN"], dec!(3.9512));
+      assert_eq!(latest.rates.len(), 6);
+
+      let requests = server.requests();
+      assert_eq!(requests.len(), 1);
+      assert_eq!(requests[0].path, "/v1/latest");
+      assert_eq!(requests[0].params["base"], "USD");
+      assert!(!requests[0].params.contains_key("api_key"));
+}
+
+#[tokio::test]
+async fn test_mock_crosses_rates_through_fixture_base() {
+      let (_server, client) = &start().await;
+      let latest = (RatiosListRequest{ client, args: RatiosListArguments{ base: "EUR".to_string() } }).call().await.unwrap();
+      assert_eq!(latest.base, "EUR");
+      assert!(latest.rates.contains_key("USD"));
+      assert!(!latest.rates.contains_key("EUR"));
+}
+
+#[tokio::test]
+async fn test_mock_reports_unknown_base_like_currencybeacon() {
+      let (_server, client) = &start().await;
+      let err = (RatiosListRequest{ client, args: RatiosListArguments{ base: "XD".to_string() } }).call().await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ codes } if codes == vec!["XD".to_string()]));
+}
+
+#[tokio::test]
+async fn test_mock_serves_historical_rates_with_symbols() {
+      let (server, client) = &start().await;
+      let historical = (HistoricalRatesRequest{ client, args: HistoricalRatesArguments{
+            date: date(4),
+            base: "USD".to_string(),
+            symbols: Some(vec!["pln".to_string(), "EUR".to_string()]),
+      } }).call().await.unwrap();
+      assert_eq!(historical.date, date(4));
+      assert_eq!(historical.rates.len(), 2);
+      assert_eq!(historical.rates["EUR"], dec!(0.9218));
+      assert_eq!(server.requests()[0].params["symbols"], "PLN,EUR");
+
+      // Sunday: the rates of Friday are effective.
+      let weekend = (HistoricalRatesRequest{ client, args: HistoricalRatesArguments{ date: date(3), base: "USD".to_string(), symbols: None } }).call().await.unwrap();
+      assert_eq!(weekend.date, date(1));
+}
+
+#[tokio::test]
+async fn test_mock_serves_timeseries() {
+      let (_server, client) = &start().await;
+      let series = (TimeSeriesRequest{ client, args: TimeSeriesArguments{
+            start_date: date(1),
+            end_date: date(6),
+            base: "USD".to_string(),
+            symbols: Some(vec!["PLN".to_string()]),
+      } }).call().await.unwrap();
+      let days: Vec<NaiveDate> = series.rates.keys().copied().collect();
+      assert_eq!(days, vec![date(1), date(4), date(5), date(6)]);
+      assert_eq!(series.rates[&date(5)]["PLN"], dec!(3.9780));
+}
+
+#[tokio::test]
+async fn test_mock_sends_every_digit_of_rates_and_amounts() {
+      let mut fixtures = Fixtures::default();
+      fixtures.latest.rates.insert("PLN".to_string(), dec!(3.9876543210987654321));
+      let server = MockServer::start(MockConfig{ fixtures, ..MockConfig::default() }).await.unwrap();
+      let client = &CurrencyBeaconClient::new(&server.credentials()).unwrap();
+
+      let latest = (RatiosListRequest{ client, args: RatiosListArguments{ base: "USD".to_string() } }).call().await.unwrap();
+      assert_eq!(latest.rates["PLN"], dec!(3.9876543210987654321));
+      let conversion = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(1234567890.123456789),
+            date: None,
+            rounding: RoundingPolicy{ places: Some(20), ..RoundingPolicy::default() },
+      } }).call().await.unwrap();
+      assert_eq!(conversion.amount, dec!(1234567890.123456789));
+      assert_eq!(conversion.value, Some(dec!(1234567890.123456789) * dec!(3.9876543210987654321)));
+}
+
+#[tokio::test]
+async fn test_mock_converts_current_and_dated_amounts() {
+      let (_server, client) = &start().await;
+      let current = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(100),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap();
+      assert_eq!(current.converted(), Some(Money::new(dec!(395.12), "PLN")));
+
+      let dated = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(100),
+            date: Some(date(1)),
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap();
+      assert_eq!(dated.converted(), Some(Money::new(dec!(398.97), "PLN")));
+
+      let same = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "usd".to_string(),
+            to: "USD".to_string(),
+            amount: dec!(100),
+            date: Some(date(5)),
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap();
+      assert_eq!(same.converted(), Some(Money::new(dec!(100), "USD")));
+      assert_eq!(same.date, "2024-03-05");
+
+      let too_large = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: Decimal::MAX,
+            date: Some(date(5)),
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap_err();
+      assert!(matches!(too_large, ApiError::InvalidArguments{ ref reason, .. } if reason == "amount too large"), "{:?}", too_large);
+
+      let unknown_same = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "XD".to_string(),
+            to: "XD".to_string(),
+            amount: dec!(1),
+            date: Some(date(5)),
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap_err();
+      assert!(matches!(unknown_same, ApiError::UnknownCurrency{ .. }));
+
+      let unknown = (ExchangeCurrencyRequest{ client, args: ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "XD".to_string(),
+            amount: dec!(1),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      } }).call().await.unwrap_err();
+      assert!(matches!(unknown, ApiError::UnknownCurrency{ .. }));
+}
+
+#[tokio::test]
+async fn test_mock_serves_currencies_and_health() {
+      let (_server, client) = &start().await;
+      let list = (CurrencyListRequest{ client }).call().await.unwrap();
+      let euro = list.currencies.iter().find(|currency| currency.short_code == "EUR").unwrap();
+      assert_eq!(euro.name, "Euro");
+      assert_eq!(euro.symbol, "€");
+      let health = (Request{ client }).call().await.unwrap();
+      assert!(health.reachable);
+      assert_eq!(health.status, Some(200));
+}
+
+#[tokio::test]
+async fn test_mock_rejects_wrong_api_key() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let credentials = Credentials{ url: server.url(), api_key: "wrong".to_string() };
+      let client = &CurrencyBeaconClient::new(&credentials).unwrap();
+      let err = (CurrencyListRequest{ client }).call().await.unwrap_err();
+      assert!(matches!(err, ApiError::Unauthorized{ .. }));
+      assert_eq!(err.status().map(|status| status.as_u16()), Some(401));
+}
+
+#[tokio::test]
+async fn test_client_cache_spares_requests_to_mock() {
+      let server = MockServer::start(MockConfig::default()).await.unwrap();
+      let client = &CurrencyBeaconClient::with_config(&server.credentials(), ClientConfig{
+            cache: Some(CacheConfig::default()),
+            ..ClientConfig::default()
+      }).unwrap();
+      let first = (RatiosListRequest{ client, args: RatiosListArguments{ base: "USD".to_string() } }).call().await.unwrap();
+      let second = (RatiosListRequest{ client, args: RatiosListArguments{ base: "usd".to_string() } }).call().await.unwrap();
+      assert!(!first.cache.from_cache);
+      assert!(second.cache.from_cache);
+      assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_currencybeacon_provider_against_mock() {
+      let (_server, client) = &start().await;
+      let provider: &dyn RateProvider = client;
+      let conversion = provider.convert(ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "JPY".to_string(),
+            amount: dec!(10),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap();
+      assert_eq!(conversion.provider, "currencybeacon");
+      assert_eq!(conversion.to, Money::new(dec!(1471), "JPY"));
+      assert_eq!(conversion.rate, Some(dec!(147.06)));
+      assert_eq!(conversion.date, date(9));
+
+      let currencies = provider.currencies().await.unwrap();
+      assert_eq!(currencies.currencies.first().map(|currency| currency.code.as_str()), Some("CAD"));
+}
@@ -0,0 +1,77 @@
+use rust_decimal_macros::dec;
+use serde_json::json;
+use task::api::models::{ConvertResponse, CurrencyList, Envelope, HistoricalResponse, LatestResponse};
+
+#[test]
+fn test_convert_response_parses_value() {
+      let envelope: Envelope<ConvertResponse> = serde_json::from_value(json!({
+            "meta": { "code": 200, "disclaimer": "Usage subject to terms" },
+            "response": { "timestamp": 1710000000, "date": "2024-03-09", "from": "USD", "to": "PLN", "amount": 100, "value": 396.5 }
+      })).unwrap();
+      assert_eq!(envelope.meta.code, 200);
+      assert_eq!(envelope.response.to, "PLN");
+      assert_eq!(envelope.response.value, Some(dec!(396.5)));
+      assert_eq!(envelope.response.converted().unwrap().to_string(), "396.5 PLN");
+}
+
+#[test]
+fn test_convert_response_empty_array_value_is_none() {
+      let envelope: Envelope<ConvertResponse> = serde_json::from_value(json!({
+            "meta": { "code": 200 },
+            "response": { "timestamp": 1710000000, "date": "2024-03-09", "from": "USD", "to": "XD", "amount": 100, "value": [] }
+      })).unwrap();
+      assert_eq!(envelope.response.value, None);
+}
+
+#[test]
+fn test_latest_response_empty_array_rates_is_empty() {
+      let envelope: Envelope<LatestResponse> = serde_json::from_value(json!({
+            "meta": { "code": 200 },
+            "response": { "date": "2024-03-09T12:00:00Z", "base": "XD", "rates": [] }
+      })).unwrap();
+      assert!(envelope.response.rates.is_empty());
+
+      let envelope: Envelope<LatestResponse> = serde_json::from_value(json!({
+            "meta": { "code": 200 },
+            "response": { "date": "2024-03-09T12:00:00Z", "base": "USD", "rates": { "PLN": 3.96, "EUR": 0.92 } }
+      })).unwrap();
+      assert_eq!(envelope.response.rates["PLN"], dec!(3.96));
+      assert_eq!(envelope.response.rates.len(), 2);
+}
+
+#[test]
+fn test_currency_list_parses_entries() {
+      let envelope: Envelope<CurrencyList> = serde_json::from_value(json!({
+            "meta": { "code": 200 },
+            "response": [
+                  { "id": 1, "name": "Polish Zloty", "short_code": "PLN", "code": "985", "precision": 2, "subunit": 100, "symbol": "zł", "symbol_first": false, "decimal_mark": ",", "thousands_separator": " " },
+                  { "id": 2, "name": "Japanese Yen", "short_code": "JPY", "precision": 0 }
+            ]
+      })).unwrap();
+      assert_eq!(envelope.response.currencies.len(), 2);
+      assert_eq!(envelope.response.currencies[0].short_code, "PLN");
+      assert_eq!(envelope.response.currencies[1].precision, 0);
+}
+
+#[test]
+fn test_amounts_keep_every_digit_of_the_json() {
+      let envelope: Envelope<ConvertResponse> = serde_json::from_str(r#"{
+            "meta": { "code": 200 },
+            "response": { "timestamp": 1710000000, "date": "2024-03-09", "from": "USD", "to": "PLN", "amount": 1234567890123456.78, "value": 4890370326355227.61 }
+      }"#).unwrap();
+      assert_eq!(envelope.response.amount, dec!(1234567890123456.78));
+      assert_eq!(envelope.response.value, Some(dec!(4890370326355227.61)));
+
+      let envelope: Envelope<LatestResponse> = serde_json::from_str(r#"{
+            "meta": { "code": 200 },
+            "response": { "date": "2024-03-09", "base": "USD", "rates": { "PLN": 3.951234567890123456, "IRR": 42075.000000000001 } }
+      }"#).unwrap();
+      assert_eq!(envelope.response.rates["PLN"], dec!(3.951234567890123456));
+      assert_eq!(envelope.response.rates["IRR"], dec!(42075.000000000001));
+
+      let envelope: Envelope<HistoricalResponse> = serde_json::from_str(r#"{
+            "meta": { "code": 200 },
+            "response": { "date": "2024-03-05", "base": "USD", "rates": { "PLN": 3.97800000000000000001 } }
+      }"#).unwrap();
+      assert_eq!(envelope.response.rates["PLN"], dec!(3.97800000000000000001));
+}
@@ -0,0 +1,43 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::ApiError;
+use task::api::models::{ConvertResponse, Envelope};
+use task::api::money::Money;
+
+#[test]
+fn test_money_keeps_every_cent_of_large_amounts() {
+      let invoice = Money::new(dec!(12345678901.23), "usd");
+      let converted = invoice.checked_convert(dec!(3.9612), "pln").unwrap();
+      assert_eq!(converted.amount, dec!(48903703263.552276));
+      assert_eq!(converted.currency, "PLN");
+      assert_eq!(converted.rounded(2).amount, dec!(48903703263.55));
+}
+
+#[test]
+fn test_money_checked_convert_reports_amounts_too_large() {
+      let huge = Money::new(Decimal::MAX, "USD");
+      let err = huge.checked_convert(dec!(3.9612), "PLN").unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ ref reason, .. } if reason == "amount too large"), "{:?}", err);
+      assert_eq!(huge.checked_convert(dec!(0.5), "PLN").unwrap().amount, Decimal::MAX * dec!(0.5));
+}
+
+#[test]
+fn test_money_rounds_half_away_from_zero() {
+      assert_eq!(Money::new(dec!(2.345), "EUR").rounded(2).amount, dec!(2.35));
+      assert_eq!(Money::new(dec!(-2.345), "EUR").rounded(2).amount, dec!(-2.35));
+      assert_eq!(Money::new(dec!(2.344), "EUR").rounded(2).amount, dec!(2.34));
+}
+
+#[test]
+fn test_money_display() {
+      assert_eq!(Money::new(dec!(100.50), "usd").to_string(), "100.50 USD");
+}
+
+#[test]
+fn test_convert_response_parses_into_decimal_without_float_noise() {
+      let envelope: Envelope<ConvertResponse> = serde_json::from_str(
+            r#"{"meta":{"code":200},"response":{"timestamp":1710000000,"date":"2024-03-09","from":"USD","to":"PLN","amount":0.1,"value":0.3}}"#
+      ).unwrap();
+      assert_eq!(envelope.response.amount, dec!(0.1));
+      assert_eq!(envelope.response.converted().unwrap(), Money::new(dec!(0.3), "PLN"));
+}
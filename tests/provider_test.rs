@@ -0,0 +1,49 @@
+mod common;
+
+use common::StubProvider;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, RatiosListArguments};
+use task::api::credentials::Credentials;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::provider::RateProvider;
+use task::api::rate_table::RateTable;
+use task::api::{ApiError, CurrencyBeaconClient};
+
+#[tokio::test]
+async fn test_providers_can_be_swapped_behind_dyn() {
+      let credentials = Credentials{ url: "https://api.currencybeacon.com".to_string(), api_key: "key".to_string() };
+      let providers: Vec<Box<dyn RateProvider>> = vec![
+            Box::new(CurrencyBeaconClient::new(&credentials).unwrap()),
+            Box::new(StubProvider::new("fixed")),
+      ];
+      let names: Vec<&str> = providers.iter().map(|provider| provider.name()).collect();
+      assert_eq!(names, vec!["currencybeacon", "fixed"]);
+
+      let conversion = providers[1].convert(ExchangeCurrencyArguments{
+            from: "USD".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(12.5),
+            date: None,
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap();
+      assert_eq!(conversion.provider, "fixed");
+      assert_eq!(conversion.to, Money::new(dec!(50.00), "PLN"));
+      assert_eq!(conversion.rate, Some(dec!(4)));
+}
+
+#[tokio::test]
+async fn test_provider_reports_unknown_currency() {
+      let provider: &dyn RateProvider = &StubProvider::new("fixed");
+      let err = provider.latest(RatiosListArguments{ base: "XD".to_string() }).await.unwrap_err();
+      assert!(matches!(err, ApiError::UnknownCurrency{ codes } if codes == vec!["XD".to_string()]));
+}
+
+#[tokio::test]
+async fn test_rate_table_from_provider_rates() {
+      let provider: &dyn RateProvider = &StubProvider::new("fixed");
+      let rates = provider.latest(RatiosListArguments{ base: "USD".to_string() }).await.unwrap();
+      let table = RateTable::from(rates);
+      assert_eq!(table.snapshot().date, "2024-03-09");
+      let conversion = table.convert(&Money::new(dec!(8), "EUR"), "PLN", RoundingPolicy::default()).unwrap();
+      assert_eq!(conversion.to, Money::new(dec!(40.00), "PLN"));
+}
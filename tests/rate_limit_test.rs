@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+use task::api::ApiError;
+use task::api::rate_limit::{LimitMode, RateLimit, RateLimiter};
+
+fn limiter(burst: u32, refill_every: Duration) -> RateLimiter {
+      RateLimiter::new(&RateLimit{ burst, refill_every, mode: LimitMode::Wait })
+}
+
+#[test]
+fn test_rate_limiter_allows_a_burst_then_refuses() {
+      let limiter = limiter(3, Duration::from_secs(60));
+      assert!(limiter.try_acquire().is_ok());
+      assert!(limiter.try_acquire().is_ok());
+      assert!(limiter.try_acquire().is_ok());
+      let wait = limiter.try_acquire().unwrap_err();
+      assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+}
+
+#[test]
+fn test_rate_limiter_refills_over_time() {
+      let limiter = limiter(1, Duration::from_millis(50));
+      assert!(limiter.try_acquire().is_ok());
+      assert!(limiter.try_acquire().is_err());
+      std::thread::sleep(Duration::from_millis(60));
+      assert!(limiter.try_acquire().is_ok());
+}
+
+#[tokio::test]
+async fn test_rate_limiter_fail_fast_returns_throttled() {
+      let limiter = limiter(1, Duration::from_secs(60));
+      limiter.acquire(LimitMode::FailFast).await.unwrap();
+      let err = limiter.acquire(LimitMode::FailFast).await.unwrap_err();
+      assert!(matches!(err, ApiError::Throttled{ .. }));
+}
+
+#[tokio::test]
+async fn test_rate_limiter_wait_mode_waits_for_a_token() {
+      let limiter = limiter(1, Duration::from_millis(100));
+      let started = Instant::now();
+      limiter.acquire(LimitMode::Wait).await.unwrap();
+      limiter.acquire(LimitMode::Wait).await.unwrap();
+      assert!(started.elapsed() >= Duration::from_millis(90));
+}
+
+#[tokio::test]
+async fn test_client_fails_fast_without_sending_once_the_limit_is_reached() {
+      use task::api::credentials::Credentials;
+      use task::api::retry::RetryPolicy;
+      use task::api::{ClientConfig, CurrencyBeaconClient, CurrencyListRequest, Requests};
+
+      // Nothing listens on port 1, so the first request fails right away with a transport error.
+      let credentials = Credentials{ url: "http://127.0.0.1:1".to_string(), api_key: "key".to_string() };
+      let config = ClientConfig{
+            retry: RetryPolicy::none(),
+            rate_limit: Some(RateLimit{ burst: 1, refill_every: Duration::from_secs(60), mode: LimitMode::FailFast }),
+            ..ClientConfig::default()
+      };
+      let client = &CurrencyBeaconClient::with_config(&credentials, config).unwrap();
+      let first = (CurrencyListRequest{ client }).call().await.unwrap_err();
+      assert!(matches!(first, ApiError::Transport(_)));
+      let second = (CurrencyListRequest{ client }).call().await.unwrap_err();
+      assert!(matches!(second, ApiError::Throttled{ .. }));
+}
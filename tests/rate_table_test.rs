@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use task::api::ApiError;
+use task::api::cache::CacheInfo;
+use task::api::models::LatestResponse;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::rate_table::{RatePath, RateTable};
+
+fn table() -> RateTable {
+      let mut rates: BTreeMap<String, Decimal> = BTreeMap::new();
+      rates.insert("USD".to_string(), dec!(1.0));
+      rates.insert("PLN".to_string(), dec!(4.0));
+      rates.insert("EUR".to_string(), dec!(0.8));
+      rates.insert("JPY".to_string(), dec!(150));
+      RateTable::from(LatestResponse{ date: "2024-03-09T12:00:00Z".to_string(), base: "usd".to_string(), rates, cache: CacheInfo::default() })
+}
+
+#[test]
+fn test_rate_table_direct_rate() {
+      let conversion = table().convert(&Money::new(dec!(100), "USD"), "pln", RoundingPolicy::default()).unwrap();
+      assert_eq!(conversion.to, Money::new(dec!(400), "PLN"));
+      assert_eq!(conversion.path, RatePath::Direct{ from: "USD".to_string(), to: "PLN".to_string() });
+      assert_eq!(conversion.snapshot.base, "USD");
+      assert_eq!(conversion.snapshot.date, "2024-03-09T12:00:00Z");
+}
+
+#[test]
+fn test_rate_table_inverse_rate() {
+      let conversion = table().convert(&Money::new(dec!(100), "PLN"), "USD", RoundingPolicy::default()).unwrap();
+      assert_eq!(conversion.to.amount, dec!(25));
+      assert_eq!(conversion.rate, dec!(0.25));
+      assert!(matches!(conversion.path, RatePath::Inverse{ .. }));
+}
+
+#[test]
+fn test_rate_table_cross_rate_through_base() {
+      let conversion = table().convert(&Money::new(dec!(100), "PLN"), "EUR", RoundingPolicy::default()).unwrap();
+      assert_eq!(conversion.to.amount, dec!(20));
+      assert_eq!(conversion.path, RatePath::Cross{ from: "PLN".to_string(), via: "USD".to_string(), to: "EUR".to_string() });
+      assert_eq!(conversion.path.to_string(), "PLN -> USD -> EUR");
+
+      let conversion = table().convert(&Money::new(dec!(10), "EUR"), "JPY", RoundingPolicy::default()).unwrap();
+      assert_eq!(conversion.to.amount, dec!(1875));
+}
+
+#[test]
+fn test_rate_table_reports_amounts_too_large_to_convert() {
+      let err = table().convert(&Money::new(Decimal::MAX, "EUR"), "JPY", RoundingPolicy::default()).unwrap_err();
+      assert!(matches!(&err, ApiError::InvalidArguments{ reason, .. } if reason == "amount too large"), "{:?}", err);
+      let conversion = table().convert(&Money::new(Decimal::MAX, "JPY"), "EUR", RoundingPolicy{ places: Some(0), ..RoundingPolicy::default() }).unwrap();
+      assert!(conversion.to.amount < Decimal::MAX);
+}
+
+#[test]
+fn test_rate_table_identity() {
+      let (rate, path) = table().rate("eur", "EUR").unwrap();
+      assert_eq!(rate, Decimal::ONE);
+      assert_eq!(path, RatePath::Identity);
+}
+
+#[test]
+fn test_rate_table_unknown_currency() {
+      let err = table().rate("PLN", "XD").unwrap_err();
+      assert!(matches!(&err, ApiError::UnknownCurrency{ codes } if codes == &vec!["XD".to_string()]));
+}
+
+#[test]
+fn test_rate_table_lists_currencies() {
+      assert_eq!(table().currencies(), vec!["EUR", "JPY", "PLN", "USD"]);
+}
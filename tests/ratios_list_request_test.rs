@@ -1,6 +1,5 @@
-pub(crate) use core::any::Any;
 use serde_json::{json, Value};
-use task::api::Requests;
+use task::api::{ApiError, Requests};
 use task::api::arguments::RatiosListArguments;
 use task::api::Result;
 pub struct RatiosListRequestRequestsMock {
@@ -9,7 +8,9 @@ pub struct RatiosListRequestRequestsMock {
 /// Mocking RatiosListRequest
 ///testing functionality by faking data instead of taking it from external API call
 impl Requests for RatiosListRequestRequestsMock {
-    async fn call(&mut self) -> Result<Box<dyn Any>> {
+    type Output = Value;
+
+    async fn call(&mut self) -> Result<Value> {
         let json =  json!({
               "USD": {
                 "USD": 1.0,
@@ -25,10 +26,10 @@ impl Requests for RatiosListRequestRequestsMock {
               }
             });
         if json[&self.args.base.to_ascii_uppercase()].is_null(){
-          return Err("Request got bad".into())
+          return Err(ApiError::UnknownCurrency { codes: vec![self.args.base.to_ascii_uppercase()] })
         }
         let value: Value = json[&self.args.base.to_ascii_uppercase()].clone();
-        Ok(Box::new(value))
+        Ok(value)
     }
 }
 
@@ -40,8 +41,8 @@ async fn test_ratios_list_request_succesful() {
           base: "USD".to_string(),
         }
       };
-      let result: Result<Box<dyn Any>> = client.call().await;
-      let value: Value = *result.unwrap().downcast::<Value>().unwrap();
+      let result: Result<Value> = client.call().await;
+      let value: Value = result.unwrap();
       assert_eq!(value["USD"], 1.0);
       assert_eq!(value["PLN"], 3.96);
       assert_eq!(value["AUD"], 1.52);
@@ -53,8 +54,8 @@ async fn test_ratios_list_request_succesful() {
           base: "PLN".to_string(),
         }
       };
-      let result: Result<Box<dyn Any>> = client.call().await;
-      let value: Value = *result.unwrap().downcast::<Value>().unwrap();
+      let result: Result<Value> = client.call().await;
+      let value: Value = result.unwrap();
       assert_eq!(value["USD"], 0.25);
       assert_eq!(value["PLN"], 1.0);
       assert_eq!(value["AUD"], 0.38);
@@ -69,5 +70,7 @@ async fn test_ratios_list_request_non_existant_currency_base_returns_err() {
       base: "XD".to_string(),
     }
   };
-  assert_eq!(*client.call().await.unwrap_err().to_string(), "Request got bad".to_string());
+  let err = client.call().await.unwrap_err();
+  assert!(matches!(&err, ApiError::UnknownCurrency { codes } if codes == &vec!["XD".to_string()]));
+  assert_eq!(err.to_string(), "unknown currency: XD".to_string());
 }
@@ -0,0 +1,74 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use reqwest::StatusCode;
+use task::api::ApiError;
+use task::api::retry::{parse_retry_after, RetryClass, RetryPolicy};
+
+fn server_error() -> ApiError {
+      ApiError::Status{ status: StatusCode::SERVICE_UNAVAILABLE, detail: None, source: None }
+}
+
+fn without_jitter() -> RetryPolicy {
+      RetryPolicy{ jitter: 0.0, base_delay: Duration::from_millis(100), max_delay: Duration::from_secs(1), max_attempts: 6, ..RetryPolicy::default() }
+}
+
+#[test]
+fn test_retry_backoff_doubles_and_is_capped() {
+      let policy = without_jitter();
+      assert_eq!(policy.delay(1, &server_error()), Some(Duration::from_millis(100)));
+      assert_eq!(policy.delay(2, &server_error()), Some(Duration::from_millis(200)));
+      assert_eq!(policy.delay(3, &server_error()), Some(Duration::from_millis(400)));
+      assert_eq!(policy.delay(5, &server_error()), Some(Duration::from_secs(1)));
+}
+
+#[test]
+fn test_retry_stops_after_max_attempts() {
+      let policy = without_jitter();
+      assert!(policy.delay(6, &server_error()).is_none());
+      assert!(RetryPolicy::none().delay(1, &server_error()).is_none());
+}
+
+#[test]
+fn test_retry_jitter_only_shortens_the_delay() {
+      let policy = RetryPolicy{ jitter: 1.0, ..without_jitter() };
+      for _ in 0..100 {
+            assert!(policy.delay(2, &server_error()).unwrap() <= Duration::from_millis(200));
+      }
+}
+
+#[test]
+fn test_retry_honors_retry_after() {
+      let policy = without_jitter();
+      let limited = ApiError::QuotaExceeded{ detail: None, retry_after: Some(Duration::from_millis(700)), source: None };
+      assert_eq!(policy.delay(1, &limited), Some(Duration::from_millis(700)));
+
+      let too_long = ApiError::QuotaExceeded{ detail: None, retry_after: Some(Duration::from_secs(3600)), source: None };
+      assert!(policy.delay(1, &too_long).is_none());
+}
+
+#[test]
+fn test_retry_after_accepts_seconds_and_http_dates() {
+      // Sun, 06 Nov 1994 08:49:37 GMT
+      let now = UNIX_EPOCH + Duration::from_secs(784111777);
+      assert_eq!(parse_retry_after(" 120 ", now), Some(Duration::from_secs(120)));
+      assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:50:07 GMT", now), Some(Duration::from_secs(30)));
+      assert_eq!(parse_retry_after("Sunday, 06-Nov-94 08:49:47 GMT", now), Some(Duration::from_secs(10)));
+      assert_eq!(parse_retry_after("Sun Nov  6 08:49:42 1994", now), Some(Duration::from_secs(5)));
+      assert_eq!(parse_retry_after("Sun, 06 Nov 1994 08:00:00 GMT", now), Some(Duration::ZERO), "a date that has passed");
+      assert_eq!(parse_retry_after("soon", now), None);
+      assert_eq!(parse_retry_after("-5", SystemTime::now()), None);
+}
+
+#[test]
+fn test_retry_skips_errors_that_retrying_cannot_fix() {
+      let policy = without_jitter();
+      assert!(policy.delay(1, &ApiError::UnknownCurrency{ codes: vec!["XD".to_string()] }).is_none());
+      assert!(policy.delay(1, &ApiError::Unauthorized{ status: StatusCode::UNAUTHORIZED, detail: None, source: None }).is_none());
+      assert!(policy.delay(1, &ApiError::Status{ status: StatusCode::BAD_REQUEST, detail: None, source: None }).is_none());
+}
+
+#[test]
+fn test_retry_only_configured_classes() {
+      let policy = RetryPolicy{ retry_on: vec![RetryClass::RateLimited], ..without_jitter() };
+      assert!(policy.delay(1, &server_error()).is_none());
+      assert_eq!(RetryClass::of(&server_error()), Some(RetryClass::ServerError));
+}
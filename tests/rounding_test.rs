@@ -0,0 +1,52 @@
+use rust_decimal_macros::dec;
+use task::api::money::{minor_units, Money, RoundingMode, RoundingPolicy};
+
+fn policy(mode: RoundingMode) -> RoundingPolicy {
+      RoundingPolicy{ mode, places: None }
+}
+
+#[test]
+fn test_minor_units_follow_iso_4217() {
+      assert_eq!(minor_units("JPY"), 0);
+      assert_eq!(minor_units("kwd"), 3);
+      assert_eq!(minor_units("USD"), 2);
+      assert_eq!(minor_units("CLF"), 4);
+}
+
+#[test]
+fn test_rounding_defaults_to_currency_minor_units() {
+      assert_eq!(Money::new(dec!(1234.5678), "JPY").round(RoundingPolicy::default()).amount, dec!(1235));
+      assert_eq!(Money::new(dec!(1.23456), "KWD").round(RoundingPolicy::default()).amount, dec!(1.235));
+      assert_eq!(Money::new(dec!(1.23456), "PLN").round(RoundingPolicy::default()).amount, dec!(1.23));
+}
+
+#[test]
+fn test_rounding_modes() {
+      let tie = Money::new(dec!(2.345), "EUR");
+      assert_eq!(tie.round(policy(RoundingMode::HalfEven)).amount, dec!(2.34));
+      assert_eq!(Money::new(dec!(2.355), "EUR").round(policy(RoundingMode::HalfEven)).amount, dec!(2.36));
+      assert_eq!(tie.round(policy(RoundingMode::HalfUp)).amount, dec!(2.35));
+      assert_eq!(tie.round(policy(RoundingMode::Floor)).amount, dec!(2.34));
+      assert_eq!(tie.round(policy(RoundingMode::Ceil)).amount, dec!(2.35));
+      assert_eq!(tie.round(policy(RoundingMode::Truncate)).amount, dec!(2.34));
+
+      let negative = Money::new(dec!(-2.341), "EUR");
+      assert_eq!(negative.round(policy(RoundingMode::Floor)).amount, dec!(-2.35));
+      assert_eq!(negative.round(policy(RoundingMode::Ceil)).amount, dec!(-2.34));
+      assert_eq!(negative.round(policy(RoundingMode::Truncate)).amount, dec!(-2.34));
+}
+
+#[test]
+fn test_rounding_explicit_places_override_minor_units() {
+      let policy = RoundingPolicy{ mode: RoundingMode::HalfEven, places: Some(4) };
+      assert_eq!(Money::new(dec!(1.234567), "JPY").round(policy).amount, dec!(1.2346));
+}
+
+#[test]
+fn test_rounding_mode_parsing() {
+      assert_eq!("half-even".parse::<RoundingMode>().unwrap(), RoundingMode::HalfEven);
+      assert_eq!("Bankers".parse::<RoundingMode>().unwrap(), RoundingMode::HalfEven);
+      assert_eq!("HALF_UP".parse::<RoundingMode>().unwrap(), RoundingMode::HalfUp);
+      assert_eq!(RoundingMode::Truncate.to_string().parse::<RoundingMode>().unwrap(), RoundingMode::Truncate);
+      assert!("nearest".parse::<RoundingMode>().is_err());
+}
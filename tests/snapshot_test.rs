@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use rust_decimal_macros::dec;
+use task::api::arguments::{ExchangeCurrencyArguments, HistoricalRatesArguments, RatiosListArguments};
+use task::api::cache::CacheInfo;
+use task::api::money::{Money, RoundingPolicy};
+use task::api::provider::{RateProvider, Rates};
+use task::api::snapshot::SnapshotProvider;
+use task::api::ApiError;
+
+fn day() -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, 3, 9).unwrap()
+}
+
+fn rates() -> Rates {
+      Rates{
+            provider: "currencybeacon".to_string(),
+            base: "USD".to_string(),
+            date: day(),
+            rates: BTreeMap::from([("PLN".to_string(), dec!(4)), ("EUR".to_string(), dec!(0.8))]),
+            cache: CacheInfo::default(),
+      }
+}
+
+#[tokio::test]
+async fn test_snapshot_without_rates_cannot_answer() {
+      let snapshot = SnapshotProvider::in_memory();
+      let err = snapshot.latest(RatiosListArguments{ base: "USD".to_string() }).await.unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ .. }));
+}
+
+#[tokio::test]
+async fn test_snapshot_answers_from_recorded_rates() {
+      let snapshot = SnapshotProvider::in_memory();
+      snapshot.record(&rates());
+      let latest = snapshot.latest(RatiosListArguments{ base: "eur".to_string() }).await.unwrap();
+      assert_eq!(latest.provider, "snapshot");
+      assert_eq!(latest.base, "EUR");
+      assert_eq!(latest.rates["PLN"], dec!(5));
+      assert_eq!(latest.rates["USD"], dec!(1.25));
+      assert!(latest.cache.from_cache);
+
+      let conversion = snapshot.convert(ExchangeCurrencyArguments{
+            from: "EUR".to_string(),
+            to: "PLN".to_string(),
+            amount: dec!(10),
+            date: Some(day()),
+            rounding: RoundingPolicy::default(),
+      }).await.unwrap();
+      assert_eq!(conversion.to, Money::new(dec!(50.00), "PLN"));
+
+      let err = snapshot.historical(HistoricalRatesArguments{ date: day().pred_opt().unwrap(), base: "USD".to_string(), symbols: None }).await.unwrap_err();
+      assert!(matches!(err, ApiError::InvalidArguments{ .. }));
+}
+
+#[tokio::test]
+async fn test_snapshot_ignores_its_own_answers() {
+      let snapshot = SnapshotProvider::in_memory();
+      snapshot.record(&rates());
+      let own = snapshot.latest(RatiosListArguments{ base: "PLN".to_string() }).await.unwrap();
+      snapshot.record(&own);
+      assert_eq!(snapshot.rates().unwrap().base, "USD");
+}
+
+#[tokio::test]
+async fn test_snapshot_survives_reopening_the_state_file() {
+      let dir = std::env::temp_dir().join(format!("task-snapshot-test-{}", std::process::id()));
+      let _ = std::fs::remove_dir_all(&dir);
+      let path = dir.join("snapshot.json");
+      SnapshotProvider::open(&path).unwrap().record(&rates());
+
+      let reopened = SnapshotProvider::open(&path).unwrap();
+      assert_eq!(reopened.rates().unwrap().rates, rates().rates);
+      let currencies = reopened.currencies().await.unwrap();
+      let codes: Vec<&str> = currencies.currencies.iter().map(|currency| currency.code.as_str()).collect();
+      assert_eq!(codes, vec!["EUR", "PLN", "USD"]);
+      let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDate;
+use serde_json::json;
+use task::api::models::TimeSeriesResponse;
+use task::api::timeseries::DateChunks;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+      NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+#[test]
+fn test_date_chunks_within_limit_is_single_chunk() {
+      let chunks: Vec<(NaiveDate, NaiveDate)> = DateChunks::new(date(2024, 1, 1), date(2024, 1, 31), 365).collect();
+      assert_eq!(chunks, vec![(date(2024, 1, 1), date(2024, 1, 31))]);
+}
+
+#[test]
+fn test_date_chunks_split_long_range_in_order() {
+      let chunks: Vec<(NaiveDate, NaiveDate)> = DateChunks::new(date(2024, 1, 1), date(2024, 1, 25), 10).collect();
+      assert_eq!(chunks, vec![
+            (date(2024, 1, 1), date(2024, 1, 10)),
+            (date(2024, 1, 11), date(2024, 1, 20)),
+            (date(2024, 1, 21), date(2024, 1, 25)),
+      ]);
+}
+
+#[test]
+fn test_date_chunks_exact_multiple_and_single_day() {
+      let chunks: Vec<(NaiveDate, NaiveDate)> = DateChunks::new(date(2024, 2, 1), date(2024, 2, 4), 2).collect();
+      assert_eq!(chunks, vec![(date(2024, 2, 1), date(2024, 2, 2)), (date(2024, 2, 3), date(2024, 2, 4))]);
+
+      let chunks: Vec<(NaiveDate, NaiveDate)> = DateChunks::new(date(2024, 2, 1), date(2024, 2, 1), 365).collect();
+      assert_eq!(chunks, vec![(date(2024, 2, 1), date(2024, 2, 1))]);
+}
+
+#[test]
+fn test_date_chunks_empty_range() {
+      assert_eq!(DateChunks::new(date(2024, 2, 2), date(2024, 2, 1), 365).count(), 0);
+}
+
+#[test]
+fn test_timeseries_response_is_ordered_by_date() {
+      let series: TimeSeriesResponse = serde_json::from_value(json!({
+            "base": "USD",
+            "start_date": "2024-01-01",
+            "end_date": "2024-01-03",
+            "rates": {
+                  "2024-01-03": { "PLN": 3.99 },
+                  "2024-01-01": { "PLN": 3.93 },
+                  "2024-01-02": { "PLN": 3.96 },
+            }
+      })).unwrap();
+      let days: Vec<NaiveDate> = series.rates.keys().copied().collect();
+      assert_eq!(days, vec![date(2024, 1, 1), date(2024, 1, 2), date(2024, 1, 3)]);
+}
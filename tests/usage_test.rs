@@ -0,0 +1,112 @@
+use chrono::NaiveDate;
+use task::api::usage::{UsageConfig, UsageTracker};
+
+fn state_file(name: &str) -> std::path::PathBuf {
+      let dir = std::env::temp_dir().join(format!("task-usage-test-{}-{}", name, std::process::id()));
+      let _ = std::fs::remove_dir_all(&dir);
+      dir.join("usage.json")
+}
+
+fn day(month: u32, day: u32) -> NaiveDate {
+      NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+}
+
+#[test]
+fn test_usage_is_counted_per_endpoint_and_month() {
+      let tracker = UsageTracker::open(UsageConfig{ state_file: None, monthly_allowance: Some(100), warn_threshold: 0.8 }).unwrap();
+      tracker.record_on("/v1/latest", day(3, 1));
+      tracker.record_on("/v1/latest", day(3, 31));
+      tracker.record_on("/v1/convert", day(3, 15));
+      tracker.record_on("/v1/latest", day(4, 1));
+
+      let march = tracker.report("2024-03");
+      assert_eq!(march.total, 3);
+      assert_eq!(march.calls["/v1/latest"], 2);
+      assert_eq!(march.calls["/v1/convert"], 1);
+      assert_eq!(march.used_fraction(), Some(0.03));
+      assert_eq!(tracker.report("2024-04").total, 1);
+      assert_eq!(tracker.report("2024-05").total, 0);
+}
+
+#[test]
+fn test_usage_survives_reopening_the_state_file() {
+      let path = state_file("reopen");
+      let config = UsageConfig{ state_file: Some(path.clone()), monthly_allowance: None, warn_threshold: 0.8 };
+      let tracker = UsageTracker::open(config.clone()).unwrap();
+      tracker.record_on("/v1/historical", day(6, 2));
+      tracker.record_on("/v1/historical", day(6, 3));
+      drop(tracker);
+
+      let reopened = UsageTracker::open(config).unwrap();
+      let june = reopened.report("2024-06");
+      assert_eq!(june.calls["/v1/historical"], 2);
+      assert_eq!(june.used_fraction(), None);
+      let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_usage_of_trackers_sharing_a_state_file_adds_up() {
+      let path = state_file("shared");
+      let config = UsageConfig{ state_file: Some(path.clone()), monthly_allowance: None, warn_threshold: 0.8 };
+      let first = UsageTracker::open(config.clone()).unwrap();
+      let second = UsageTracker::open(config.clone()).unwrap();
+      first.record_on("/v1/latest", day(7, 1));
+      second.record_on("/v1/latest", day(7, 1));
+      first.record_on("/v1/convert", day(7, 2));
+      assert!(!path.exists(), "requests are only counted in memory until a flush");
+      assert_eq!(second.report("2024-07").total, 1);
+      first.flush();
+      second.flush();
+      assert_eq!(second.report("2024-07").total, 3);
+      first.flush();
+      assert_eq!(first.report("2024-07").total, 2, "the counts of the file are only read back when writing");
+
+      std::thread::scope(|scope| {
+            for _ in 0..4 {
+                  let config = config.clone();
+                  scope.spawn(move || {
+                        let tracker = UsageTracker::open(config).unwrap();
+                        for _ in 0..25 {
+                              tracker.record_on("/v1/latest", day(7, 3));
+                        }
+                  });
+            }
+      });
+      drop((first, second));
+      let july = UsageTracker::open(config).unwrap().report("2024-07");
+      assert_eq!(july.calls["/v1/latest"], 102);
+      assert_eq!(july.calls["/v1/convert"], 1);
+      assert!(!path.with_file_name("usage.json.lock").exists());
+      let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_usage_rejects_corrupt_state_file() {
+      let path = state_file("corrupt");
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(&path, "not json").unwrap();
+      let config = UsageConfig{ state_file: Some(path.clone()), monthly_allowance: None, warn_threshold: 0.8 };
+      assert!(UsageTracker::open(config).is_err());
+      let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn test_usage_without_allowance_is_over_threshold_from_the_first_request() {
+      let tracker = UsageTracker::open(UsageConfig{ state_file: None, monthly_allowance: Some(0), warn_threshold: 0.8 }).unwrap();
+      assert!(tracker.over_threshold());
+      tracker.record("/v1/latest");
+      assert!(tracker.over_threshold());
+      assert_eq!(tracker.current().used_fraction(), Some(1.0));
+}
+
+#[test]
+fn test_usage_over_threshold() {
+      let tracker = UsageTracker::open(UsageConfig{ state_file: None, monthly_allowance: Some(5), warn_threshold: 0.8 }).unwrap();
+      for _ in 0..3 {
+            tracker.record("/v1/latest");
+      }
+      assert!(!tracker.over_threshold());
+      tracker.record("/v1/latest");
+      assert!(tracker.over_threshold());
+      assert_eq!(tracker.current().total, 4);
+}